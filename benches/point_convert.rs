@@ -0,0 +1,93 @@
+//! Compares the bulk conversions into the structure-of-arrays buffers with their scalar baselines.
+//!
+//! Run with `cargo bench --bench point_convert`.
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use livox2::types::{
+    ethernet::{CartesianHighPoint, CartesianLowPoint, SphericalPoint},
+    soa::{self, SoaPoints, SoaPointsMut},
+};
+use zerocopy::TryFromBytes;
+
+/// Points of 1000 packets.
+const POINTS: usize = 96 * 1000;
+const ROUNDS: u32 = 200;
+
+/// Pseudo random bytes of the points, the spherical angles are kept in range.
+fn point_bytes(stride: usize, spherical: bool) -> Vec<u8> {
+    let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+    let mut bytes: Vec<u8> = (0..POINTS * stride)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed as u8
+        })
+        .collect();
+    if spherical {
+        for point in bytes.chunks_exact_mut(stride) {
+            let theta = u16::from_le_bytes([point[4], point[5]]) % 18001;
+            let phi = u16::from_le_bytes([point[6], point[7]]) % 36001;
+            point[4..6].copy_from_slice(&theta.to_le_bytes());
+            point[6..8].copy_from_slice(&phi.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+fn bench(name: &str, mut convert: impl FnMut(SoaPointsMut) -> usize) -> Duration {
+    let mut out = SoaPoints::default();
+    out.extend_with(POINTS, |out| out.len());
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(convert(black_box(out.as_mut())));
+    }
+    let elapsed = start.elapsed() / ROUNDS;
+    println!(
+        "{name:>24}: {elapsed:?} per {POINTS} points, {:.0} Mpoints/s",
+        POINTS as f64 / elapsed.as_secs_f64() * 1e-6
+    );
+    elapsed
+}
+
+fn compare(
+    name: &str,
+    mut convert: impl FnMut(SoaPointsMut) -> usize,
+    mut scalar: impl FnMut(SoaPointsMut) -> usize,
+) {
+    let scalar = bench(&format!("{name} scalar"), &mut scalar);
+    let vectorized = bench(name, &mut convert);
+    println!(
+        "{name:>24}: {:.2}x",
+        scalar.as_secs_f64() / vectorized.as_secs_f64()
+    );
+}
+
+fn main() {
+    let bytes = point_bytes(size_of::<CartesianHighPoint>(), false);
+    let points = <[CartesianHighPoint]>::try_ref_from_bytes(&bytes).unwrap();
+    compare(
+        "cartesian high",
+        |out| soa::convert_cartesian_high(points, out),
+        |out| soa::scalar::convert_cartesian_high(points, out),
+    );
+
+    let bytes = point_bytes(size_of::<CartesianLowPoint>(), false);
+    let points = <[CartesianLowPoint]>::try_ref_from_bytes(&bytes).unwrap();
+    compare(
+        "cartesian low",
+        |out| soa::convert_cartesian_low(points, out),
+        |out| soa::scalar::convert_cartesian_low(points, out),
+    );
+
+    let bytes = point_bytes(size_of::<SphericalPoint>(), true);
+    let points = <[SphericalPoint]>::try_ref_from_bytes(&bytes).unwrap();
+    compare(
+        "spherical",
+        |out| soa::convert_spherical(points, out),
+        |out| soa::scalar::convert_spherical(points, out),
+    );
+}
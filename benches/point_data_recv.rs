@@ -0,0 +1,136 @@
+//! Compares the receiving throughput of the [`PointDataPort`], batched or not, and the [`UringPointDataPort`] on loopback.
+//!
+//! Run with `cargo bench --features io-uring --bench point_data_recv`.
+use std::{
+    net::{SocketAddr, UdpSocket},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use livox2::lidar_port::{PacketBatch, PointDataPort, UringPointDataPort};
+
+const PACKETS: u32 = 200_000;
+const DOT_NUM: u16 = 96;
+/// Header and 96 cartesian high points.
+const PACKET_LEN: usize = 36 + DOT_NUM as usize * 14;
+
+fn point_packet() -> Vec<u8> {
+    let mut packet = vec![0; PACKET_LEN];
+    packet[1..3].copy_from_slice(&(PACKET_LEN as u16).to_le_bytes());
+    packet[5..7].copy_from_slice(&DOT_NUM.to_le_bytes());
+    // CartesianCoordinateHighData
+    packet[10] = 1;
+    packet
+}
+
+/// Keeps sending point packets to the `target` until the returned flag is set.
+fn spawn_sender(sender_addr: SocketAddr, target: SocketAddr) -> Arc<AtomicBool> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let socket = UdpSocket::bind(sender_addr).unwrap();
+    socket.connect(target).unwrap();
+    thread::spawn({
+        let stop = stop.clone();
+        move || {
+            let mut packet = point_packet();
+            let mut udp_cnt = 0u16;
+            while !stop.load(Ordering::Relaxed) {
+                packet[7..9].copy_from_slice(&udp_cnt.to_le_bytes());
+                let _ = socket.send(&packet);
+                udp_cnt = udp_cnt.wrapping_add(1);
+            }
+        }
+    });
+    stop
+}
+
+fn report(name: &str, elapsed: Duration, points: u64) {
+    let secs = elapsed.as_secs_f64();
+    println!(
+        "{name:>10}: {PACKETS} packets in {elapsed:?}, {:.0} packets/s, {:.0} points/s",
+        PACKETS as f64 / secs,
+        points as f64 / secs,
+    );
+}
+
+fn bench_async_net() {
+    let (local, lidar) = ("127.0.0.1:57301", "127.0.0.1:57300");
+    smol::block_on(async {
+        let mut port = PointDataPort::new(
+            local.parse::<SocketAddr>().unwrap(),
+            lidar.parse::<SocketAddr>().unwrap(),
+            PACKET_LEN,
+        )
+        .await
+        .unwrap();
+        let stop = spawn_sender(lidar.parse().unwrap(), local.parse().unwrap());
+
+        let start = Instant::now();
+        let mut points = 0;
+        for _ in 0..PACKETS {
+            let packet = port.next_packet_ref().await.unwrap();
+            points += packet.header.dot_num as u64;
+        }
+        report("async-net", start.elapsed(), points);
+        stop.store(true, Ordering::Relaxed);
+    });
+}
+
+fn bench_recvmmsg() {
+    let (local, lidar) = ("127.0.0.1:57321", "127.0.0.1:57320");
+    smol::block_on(async {
+        let mut port = PointDataPort::new(
+            local.parse::<SocketAddr>().unwrap(),
+            lidar.parse::<SocketAddr>().unwrap(),
+            PACKET_LEN,
+        )
+        .await
+        .unwrap();
+        let mut batch = PacketBatch::new(64, PACKET_LEN);
+        let stop = spawn_sender(lidar.parse().unwrap(), local.parse().unwrap());
+
+        let start = Instant::now();
+        let mut packets = 0;
+        let mut points = 0;
+        while packets < PACKETS {
+            for packet in port.next_packet_batch(&mut batch).await.unwrap() {
+                points += packet.unwrap().header.dot_num as u64;
+                packets += 1;
+            }
+        }
+        report("recvmmsg", start.elapsed(), points);
+        stop.store(true, Ordering::Relaxed);
+    });
+}
+
+fn bench_io_uring() {
+    let (local, lidar) = ("127.0.0.1:57311", "127.0.0.1:57310");
+    smol::block_on(async {
+        let mut port = UringPointDataPort::new(
+            local.parse::<SocketAddr>().unwrap(),
+            lidar.parse::<SocketAddr>().unwrap(),
+            512,
+            PACKET_LEN,
+        )
+        .unwrap();
+        let stop = spawn_sender(lidar.parse().unwrap(), local.parse().unwrap());
+
+        let start = Instant::now();
+        let mut points = 0;
+        for _ in 0..PACKETS {
+            let packet = port.next_packet_ref().await.unwrap();
+            points += packet.header.dot_num as u64;
+        }
+        report("io_uring", start.elapsed(), points);
+        stop.store(true, Ordering::Relaxed);
+    });
+}
+
+fn main() {
+    bench_async_net();
+    bench_recvmmsg();
+    bench_io_uring();
+}
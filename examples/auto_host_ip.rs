@@ -0,0 +1,12 @@
+use livox2::lidar_port::IpConfig;
+
+fn main() -> Result<(), std::io::Error> {
+    let ip_config = IpConfig::from_lidar_ip([192, 168, 1, 101])?;
+    dbg!(&ip_config);
+    smol::block_on(async {
+        let mut point_port = ip_config.new_default_point_data_port().await?;
+        let packet = point_port.next_packet_ref().await?;
+        dbg!(packet.header);
+        Ok(())
+    })
+}
@@ -0,0 +1,12 @@
+use std::time::Duration;
+
+use livox2::lidar_port::IpConfig;
+
+fn main() -> Result<(), std::io::Error> {
+    let mut point_port = IpConfig::new([192, 168, 1, 100], [192, 168, 1, 101])
+        .new_default_blocking_point_data_port()?;
+    point_port.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let packet = point_port.next_packet_ref()?;
+    dbg!(packet.header);
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use livox2::{lidar_port::IpConfig, types::key_value::ConfigParam};
+
+fn main() -> Result<(), std::io::Error> {
+    smol::block_on(async {
+        let control_port = IpConfig::new([192, 168, 1, 100], [192, 168, 1, 101])
+            .new_default_control_port()
+            .await?;
+        control_port
+            .config_params(&[ConfigParam::PclDataType(1), ConfigParam::PointSendEn(1)])
+            .await?;
+        Ok(())
+    })
+}
@@ -0,0 +1,12 @@
+use std::time::Duration;
+
+use livox2::lidar_port::Discovery;
+
+fn main() -> Result<(), std::io::Error> {
+    smol::block_on(async {
+        let mut discovery = Discovery::new_default([192, 168, 1, 100]).await?;
+        let devices = discovery.discover(Duration::from_secs(1)).await?;
+        dbg!(devices);
+        Ok(())
+    })
+}
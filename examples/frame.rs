@@ -0,0 +1,23 @@
+use std::{pin::pin, time::Duration};
+
+use futures_lite::StreamExt;
+use livox2::lidar_port::{FrameSplit, IpConfig};
+
+fn main() -> Result<(), std::io::Error> {
+    smol::block_on(async {
+        let point_port = IpConfig::new([192, 168, 1, 100], [192, 168, 1, 101])
+            .new_default_point_data_port()
+            .await?;
+        let mut frames =
+            pin!(point_port.into_frame_stream(FrameSplit::TimeWindow(Duration::from_millis(100))));
+        while let Some(frame) = frames.next().await {
+            println!(
+                "{} points in {} packets, {} packets missing",
+                frame.points.len(),
+                frame.packet_count,
+                frame.missing_packets()
+            );
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,22 @@
+use livox2::{lidar_port::IpConfig, types::key_value::ParamKey};
+
+fn main() -> Result<(), std::io::Error> {
+    smol::block_on(async {
+        let control_port = IpConfig::new([192, 168, 1, 100], [192, 168, 1, 101])
+            .new_default_control_port()
+            .await?;
+        let info = control_port
+            .inquire(&[
+                ParamKey::Sn,
+                ParamKey::VersionApp,
+                ParamKey::Mac,
+                ParamKey::CoreTemp,
+                ParamKey::TimeOffset,
+                ParamKey::HmsCode,
+            ])
+            .await?;
+        dbg!(info.sn_str());
+        dbg!(&info);
+        Ok(())
+    })
+}
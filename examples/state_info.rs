@@ -0,0 +1,13 @@
+use livox2::lidar_port::IpConfig;
+
+fn main() -> Result<(), std::io::Error> {
+    smol::block_on(async {
+        let mut state_info_port = IpConfig::new([192, 168, 1, 100], [192, 168, 1, 101])
+            .new_default_state_info_port()
+            .await?;
+        let packet = state_info_port.next_packet_ref().await?;
+        dbg!(packet.header);
+        dbg!(packet.to_state_info()?);
+        Ok(())
+    })
+}
@@ -0,0 +1,14 @@
+use livox2::{lidar_port::PointDataPort, runtime::Tokio};
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), std::io::Error> {
+    let mut point_port = PointDataPort::<Tokio>::new_with_runtime(
+        ([192, 168, 1, 100], 56301),
+        ([192, 168, 1, 101], 56300),
+        1380,
+    )
+    .await?;
+    let packet = point_port.next_packet_ref().await?;
+    dbg!(packet.header);
+    Ok(())
+}
@@ -0,0 +1,360 @@
+//! The blocking twins of the lidar ports, based on [`std::net::UdpSocket`] without any async runtime.
+//!
+//! The packets are parsed in the same way as the async ports,
+//! and the receiving can be bounded by [`set_read_timeout`](PointDataPort::set_read_timeout),
+//! a timed out receiving returns [`Error::Timeout`](crate::Error::Timeout).
+use std::{
+    io,
+    net::{Ipv4Addr, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use zerocopy::IntoBytes;
+
+use crate::{
+    lidar_port::{
+        CrcCheck, IpConfig, PortStats, RetryPolicy, SocketPortConfig,
+        detection::{LidarSearchAckRef, LidarSearchCmdPacket},
+        imu::ImuPacketRef,
+        point_data::PointPacketRef,
+        stats::StatsTracker,
+    },
+    types::sdk_packet::{CommandType, SdkPacketHeader, SendType},
+};
+
+/// Blocking twin of the [`lidar_port::PointDataPort`](crate::lidar_port::PointDataPort).
+pub struct PointDataPort {
+    socket: UdpSocket,
+    buffer: Vec<u8>,
+    crc_check: CrcCheck,
+    stats: StatsTracker,
+}
+
+impl PointDataPort {
+    const DEFAULT_BUFFER_INIT_SIZE: usize = 1380;
+
+    pub fn new(
+        local_addr: impl ToSocketAddrs,
+        lidar_addr: impl ToSocketAddrs,
+        buffer_init_size: usize,
+    ) -> crate::Result<Self> {
+        Ok(Self {
+            socket: connect(local_addr, lidar_addr)?,
+            buffer: vec![0; buffer_init_size],
+            crc_check: CrcCheck::default(),
+            stats: StatsTracker::default(),
+        })
+    }
+
+    /// Sets how the received packets are verified, see also [`CrcCheck`].
+    pub fn with_crc_check(mut self, crc_check: CrcCheck) -> Self {
+        self.crc_check = crc_check;
+        self
+    }
+
+    /// Number of the packets failing the CRC verification so far.
+    pub fn crc_failures(&self) -> u64 {
+        self.stats.snapshot().crc_failures
+    }
+
+    /// Returns a snapshot of the counters since the port was created.
+    pub fn stats(&self) -> PortStats {
+        self.stats.snapshot()
+    }
+
+    /// Sets the timeout of [`next_packet_ref`](Self::next_packet_ref), `None` blocks indefinitely.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> crate::Result<()> {
+        Ok(self.socket.set_read_timeout(timeout)?)
+    }
+
+    pub fn next_packet_ref(&mut self) -> crate::Result<PointPacketRef<'_>> {
+        let len = loop {
+            let len = recv(&self.socket, &mut self.buffer)?;
+            if self.stats.accept(self.crc_check, &self.buffer[..len])? {
+                break len;
+            }
+        };
+        self.stats.record_parsed(
+            PointPacketRef::try_from_bytes(&self.buffer[..len]),
+            |packet| packet.header,
+        )
+    }
+}
+
+/// Blocking twin of the [`lidar_port::ImuPort`](crate::lidar_port::ImuPort).
+pub struct ImuPort {
+    socket: UdpSocket,
+    buffer: Vec<u8>,
+    crc_check: CrcCheck,
+    stats: StatsTracker,
+}
+
+impl ImuPort {
+    const DEFAULT_BUFFER_INIT_SIZE: usize = 60;
+
+    pub fn new(
+        local_addr: impl ToSocketAddrs,
+        lidar_addr: impl ToSocketAddrs,
+        buffer_init_size: usize,
+    ) -> crate::Result<Self> {
+        Ok(Self {
+            socket: connect(local_addr, lidar_addr)?,
+            buffer: vec![0; buffer_init_size],
+            crc_check: CrcCheck::default(),
+            stats: StatsTracker::default(),
+        })
+    }
+
+    /// Sets how the received packets are verified, see also [`CrcCheck`].
+    pub fn with_crc_check(mut self, crc_check: CrcCheck) -> Self {
+        self.crc_check = crc_check;
+        self
+    }
+
+    /// Number of the packets failing the CRC verification so far.
+    pub fn crc_failures(&self) -> u64 {
+        self.stats.snapshot().crc_failures
+    }
+
+    /// Returns a snapshot of the counters since the port was created.
+    pub fn stats(&self) -> PortStats {
+        self.stats.snapshot()
+    }
+
+    /// Sets the timeout of [`next_packet_ref`](Self::next_packet_ref), `None` blocks indefinitely.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> crate::Result<()> {
+        Ok(self.socket.set_read_timeout(timeout)?)
+    }
+
+    pub fn next_packet_ref(&mut self) -> crate::Result<ImuPacketRef<'_>> {
+        let len = loop {
+            let len = recv(&self.socket, &mut self.buffer)?;
+            if self.stats.accept(self.crc_check, &self.buffer[..len])? {
+                break len;
+            }
+        };
+        self.stats.record_parsed(
+            ImuPacketRef::try_from_bytes(&self.buffer[..len]),
+            |packet| packet.header,
+        )
+    }
+}
+
+/// Blocking twin of the [`lidar_port::DetectionPort`](crate::lidar_port::DetectionPort),
+/// each attempt is bounded by the [`RetryPolicy::timeout`] instead of the read timeout.
+pub struct DetectionPort {
+    socket: UdpSocket,
+    broadcast_socket: UdpSocket,
+    buffer: Vec<u8>,
+    retry_policy: RetryPolicy,
+}
+
+impl DetectionPort {
+    const DEFAULT_BUFFER_INIT_SIZE: usize = 48;
+
+    pub fn new(
+        local_ip: impl Into<Ipv4Addr>,
+        lidar_ip: impl Into<Ipv4Addr>,
+        detection_port: u16,
+        buffer_init_size: usize,
+    ) -> crate::Result<Self> {
+        let broadcast_socket = UdpSocket::bind((Ipv4Addr::BROADCAST, detection_port))?;
+
+        let socket = UdpSocket::bind((local_ip.into(), detection_port))?;
+        socket.set_broadcast(true)?;
+        socket.connect((lidar_ip.into(), detection_port))?;
+
+        Ok(Self {
+            socket,
+            broadcast_socket,
+            buffer: vec![0; buffer_init_size],
+            retry_policy: RetryPolicy::default(),
+        })
+    }
+
+    /// Sets the timeout and retry policy of the detection, see also [`RetryPolicy::default`].
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// # Error
+    ///
+    /// Returns [`Error::Timeout`](crate::Error::Timeout) if the lidar does not response in time.
+    pub fn next_packet_ref(&mut self) -> crate::Result<LidarSearchAckRef<'_>> {
+        let packet = LidarSearchCmdPacket::new(CommandType::Cmd, SendType::HostSend);
+        let seq_num = packet.header.seq_num;
+        let dst_addr = self.socket.peer_addr()?;
+
+        let Self {
+            socket,
+            broadcast_socket,
+            buffer,
+            retry_policy,
+        } = self;
+        let len = retry_policy.run_blocking(|deadline| {
+            if let Err(err) = socket.send(packet.as_bytes()) {
+                return Some(Err(err.into()));
+            }
+            loop {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return None;
+                }
+                if let Err(err) = broadcast_socket.set_read_timeout(Some(remaining)) {
+                    return Some(Err(err.into()));
+                }
+                let (len, src) = match broadcast_socket.recv_from(buffer) {
+                    Ok(received) => received,
+                    Err(err) if is_timeout(&err) => return None,
+                    Err(err) => return Some(Err(err.into())),
+                };
+                let is_ack =
+                    SdkPacketHeader::try_ref_from_frame(&buffer[..len]).is_ok_and(|(header, _)| {
+                        let (cmd_type, ack_seq_num) = (header.cmd_type, header.seq_num);
+                        cmd_type == CommandType::Ack && ack_seq_num == seq_num
+                    });
+                if src == dst_addr && is_ack {
+                    return Some(Ok(len));
+                }
+            }
+        })?;
+
+        LidarSearchAckRef::try_from_bytes(&buffer[..len])
+    }
+}
+
+fn connect(
+    local_addr: impl ToSocketAddrs,
+    lidar_addr: impl ToSocketAddrs,
+) -> crate::Result<UdpSocket> {
+    let socket = UdpSocket::bind(local_addr)?;
+    socket.connect(lidar_addr)?;
+    Ok(socket)
+}
+
+/// The timed out read returns `WouldBlock` on unix but `TimedOut` on windows.
+fn is_timeout(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+/// Receives from the connected socket, the timeout is reported as [`Error::Timeout`](crate::Error::Timeout).
+fn recv(socket: &UdpSocket, buffer: &mut [u8]) -> crate::Result<usize> {
+    socket.recv(buffer).map_err(|err| {
+        if is_timeout(&err) {
+            crate::Error::Timeout { attempts: 1 }
+        } else {
+            err.into()
+        }
+    })
+}
+
+impl IpConfig {
+    pub fn new_blocking_point_data_port(
+        &self,
+        point_data_port: &SocketPortConfig,
+        buffer_init_size: usize,
+    ) -> crate::Result<PointDataPort> {
+        PointDataPort::new(
+            (self.local, point_data_port.local),
+            (self.lidar, point_data_port.lidar),
+            buffer_init_size,
+        )
+    }
+    pub fn new_default_blocking_point_data_port(&self) -> crate::Result<PointDataPort> {
+        self.new_blocking_point_data_port(
+            &SocketPortConfig::new_point_data_config(),
+            PointDataPort::DEFAULT_BUFFER_INIT_SIZE,
+        )
+    }
+
+    pub fn new_blocking_imu_port(
+        &self,
+        imu_port: &SocketPortConfig,
+        buffer_init_size: usize,
+    ) -> crate::Result<ImuPort> {
+        ImuPort::new(
+            (self.local, imu_port.local),
+            (self.lidar, imu_port.lidar),
+            buffer_init_size,
+        )
+    }
+    pub fn new_default_blocking_imu_port(&self) -> crate::Result<ImuPort> {
+        self.new_blocking_imu_port(
+            &SocketPortConfig::new_imu_port_config(),
+            ImuPort::DEFAULT_BUFFER_INIT_SIZE,
+        )
+    }
+
+    pub fn new_blocking_detection_port(
+        &self,
+        detection_port: u16,
+        buffer_init_size: usize,
+    ) -> crate::Result<DetectionPort> {
+        DetectionPort::new(self.local, self.lidar, detection_port, buffer_init_size)
+    }
+    pub fn new_default_blocking_detection_port(&self) -> crate::Result<DetectionPort> {
+        self.new_blocking_detection_port(
+            SocketPortConfig::new_detection_port_config().lidar,
+            DetectionPort::DEFAULT_BUFFER_INIT_SIZE,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::types::sdk_packet::{LivoxLidarDeviceType, QueryDeviceTypeAck, SdkFrame};
+
+    #[test]
+    fn read_timeout() {
+        let lidar = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut port =
+            PointDataPort::new("127.0.0.1:0", lidar.local_addr().unwrap(), 1380).unwrap();
+        port.set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        assert!(matches!(
+            port.next_packet_ref(),
+            Err(crate::Error::Timeout { attempts: 1 })
+        ));
+    }
+
+    /// The lidar on another loopback address broadcasts the ACK, as the real one does.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn detection_round_trip() {
+        const DETECTION_PORT: u16 = 56987;
+        let lidar = UdpSocket::bind(("127.0.0.2", DETECTION_PORT)).unwrap();
+        lidar.set_broadcast(true).unwrap();
+        let handle = thread::spawn(move || {
+            let mut buffer = [0; SdkFrame::MAX_LEN];
+            let len = lidar.recv(&mut buffer).unwrap();
+            let request = SdkFrame::parse(&buffer[..len]).unwrap();
+            let ack = QueryDeviceTypeAck {
+                ret_code: 0,
+                dev_type: LivoxLidarDeviceType::Mid360,
+                sn: *b"47MDL9T0020193\0\0",
+                lidar_ip: [127, 0, 0, 2],
+                cmd_port: 56100,
+            };
+            let ack = SdkFrame::encode_reply(request.header, ack.as_bytes()).unwrap();
+            lidar
+                .send_to(&ack, (Ipv4Addr::BROADCAST, DETECTION_PORT))
+                .unwrap();
+        });
+
+        let mut port = DetectionPort::new([127, 0, 0, 1], [127, 0, 0, 2], DETECTION_PORT, 48)
+            .unwrap()
+            .with_retry_policy(RetryPolicy::no_retry(Duration::from_secs(1)));
+        let ack = port.next_packet_ref().unwrap();
+        assert_eq!({ ack.header.cmd_type }, CommandType::Ack);
+        assert_eq!(ack.data.lidar_ip, [127, 0, 0, 2]);
+        assert_eq!(&ack.data.sn[..14], b"47MDL9T0020193");
+        handle.join().unwrap();
+    }
+}
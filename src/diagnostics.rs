@@ -0,0 +1,262 @@
+//! Decoding of the lidar health codes, i.e. the [`hms_code`](crate::types::state_info::LidarStateInfo::hms_code)
+//! and the [`status_code`](crate::types::state_info::LidarStateInfo::status_code) of the Mid-360 and HAP.
+//!
+//! Each code is a 32-bit value, bit 0-7 is the abnormal level and bit 16-31 is the abnormal ID.
+//! For details, see the `hms_code` of [`Livox Ethernet Protocol`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html).
+use std::fmt::Display;
+
+use crate::types::{DirectLidarStateInfo, state_info::LidarStateInfo};
+
+/// Number of the codes in the `hms_code` and the `status_code`.
+pub const SLOT_COUNT: usize = 8;
+
+/// The abnormal level of a [`HmsCode`], ordered by the severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+    Fatal,
+}
+
+impl Severity {
+    const fn from_level(level: u8) -> Option<Self> {
+        match level {
+            0x01 => Some(Self::Info),
+            0x02 => Some(Self::Warning),
+            0x03 => Some(Self::Error),
+            0x04 => Some(Self::Fatal),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Info => write!(f, "Info"),
+            Self::Warning => write!(f, "Warning"),
+            Self::Error => write!(f, "Error"),
+            Self::Fatal => write!(f, "Fatal"),
+        }
+    }
+}
+
+/// A raw diagnostic code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(transparent)]
+pub struct HmsCode(pub u32);
+
+impl From<u32> for HmsCode {
+    fn from(code: u32) -> Self {
+        Self(code)
+    }
+}
+
+impl HmsCode {
+    /// The abnormal ID, `0` if there is no abnormality.
+    pub const fn abnormal_id(self) -> u16 {
+        (self.0 >> 16) as u16
+    }
+
+    /// The raw abnormal level, see also [`severity`](Self::severity).
+    pub const fn level(self) -> u8 {
+        self.0 as u8
+    }
+
+    /// Returns `None` if there is no abnormality, or the level is not documented.
+    pub const fn severity(self) -> Option<Severity> {
+        Severity::from_level(self.level())
+    }
+
+    pub const fn is_abnormal(self) -> bool {
+        self.0 != 0
+    }
+
+    /// The human-readable description of the [`abnormal_id`](Self::abnormal_id).
+    pub const fn description(self) -> &'static str {
+        match self.abnormal_id() {
+            0x0000 => "No abnormality",
+            0x0102 => "The environment temperature is relatively high",
+            0x0103 => "The environment temperature is too high",
+            0x0104 => {
+                "The window is dirty, which will influence the reliability of the point cloud"
+            }
+            0x0105 => "An error occurred during the device upgrade",
+            0x0111 | 0x0112 => "Abnormal temperature of the internal components",
+            0x0113 => "The IMU stopped working",
+            0x0114 => "The environment temperature is too high, the device is degraded",
+            0x0115 => {
+                "The environment temperature beyond the limit, the device has stopped working"
+            }
+            0x0116 => "Abnormal external voltage",
+            0x0117 => "Abnormal lidar parameters",
+            0x0201 => "The scan module is heating",
+            0x0210..=0x0214 => "The scan module is abnormal",
+            0x0401 => "Command line connection detected",
+            0x0402 => "The communication link was down and is recovered",
+            0x0403 => "The PTP time synchronization stopped or the time gap is too big",
+            0x0404 => "The PTP version 1588-v2.1 is not supported",
+            0x0405 => "The PPS time synchronization is abnormal",
+            0x0406 => "The time synchronization is abnormal",
+            0x0407 => "The PTP time source is unstable",
+            0x0408 => "The GPS time synchronization is abnormal",
+            _ => "Unknown abnormality",
+        }
+    }
+}
+
+impl Display for HmsCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.severity() {
+            Some(severity) => write!(f, "[{severity}] ")?,
+            None => write!(f, "[Level {:#04X}] ", self.level())?,
+        }
+        write!(f, "{:#06X}: {}", self.abnormal_id(), self.description())
+    }
+}
+
+/// An abnormal code at its slot of the `hms_code` or the `status_code`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    /// Index of the code, in [0, [`SLOT_COUNT`]).
+    pub slot: usize,
+    pub code: HmsCode,
+}
+
+/// Returns the abnormal codes of the `hms_code`.
+pub fn decode_hms(hms_code: &[u32; SLOT_COUNT]) -> impl Iterator<Item = Diagnostic> + '_ {
+    abnormal_slots(hms_code.iter().map(|&code| HmsCode(code)))
+}
+
+/// Splits the 32-byte `status_code` into its little endian slots, each in the same layout as the `hms_code`.
+pub fn status_slots(status_code: &[u8; 32]) -> [HmsCode; SLOT_COUNT] {
+    let (slots, _) = status_code.as_chunks::<4>();
+    std::array::from_fn(|slot| HmsCode(u32::from_le_bytes(slots[slot])))
+}
+
+impl LidarStateInfo {
+    /// The abnormal codes of the [`hms_code`](Self::hms_code), empty if it has not been received.
+    pub fn diagnostics(&self) -> impl Iterator<Item = Diagnostic> + '_ {
+        self.hms_code.iter().flat_map(decode_hms)
+    }
+
+    /// The abnormal slots of the [`status_code`](Self::status_code), empty if it has not been received.
+    pub fn status_diagnostics(&self) -> impl Iterator<Item = Diagnostic> + '_ {
+        self.status_code
+            .iter()
+            .flat_map(|status_code| abnormal_slots(status_slots(status_code)))
+    }
+
+    /// The most severe level of the [`diagnostics`](Self::diagnostics),
+    /// `None` if there is no abnormality or the `hms_code` has not been received.
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.diagnostics()
+            .filter_map(|diagnostic| diagnostic.code.severity())
+            .max()
+    }
+}
+
+impl DirectLidarStateInfo {
+    /// The abnormal code of the [`hms_code`](Self::hms_code), which only holds a single slot.
+    pub fn diagnostics(&self) -> impl Iterator<Item = Diagnostic> + use<> {
+        abnormal_slots([HmsCode(self.hms_code)])
+    }
+
+    /// The abnormal slots of the [`status_code`](Self::status_code).
+    pub fn status_diagnostics(&self) -> impl Iterator<Item = Diagnostic> + use<> {
+        abnormal_slots(status_slots(&self.status_code))
+    }
+
+    /// The most severe level of the [`diagnostics`](Self::diagnostics), `None` if there is no abnormality.
+    pub fn max_severity(&self) -> Option<Severity> {
+        HmsCode(self.hms_code).severity()
+    }
+}
+
+fn abnormal_slots(codes: impl IntoIterator<Item = HmsCode>) -> impl Iterator<Item = Diagnostic> {
+    codes
+        .into_iter()
+        .enumerate()
+        .filter(|(_, code)| code.is_abnormal())
+        .map(|(slot, code)| Diagnostic { slot, code })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hms_code_fields() {
+        let code = HmsCode(0x0103_0003);
+        assert_eq!(code.abnormal_id(), 0x0103);
+        assert_eq!(code.level(), 0x03);
+        assert_eq!(code.severity(), Some(Severity::Error));
+        assert_eq!(
+            code.to_string(),
+            "[Error] 0x0103: The environment temperature is too high"
+        );
+
+        let unknown = HmsCode(0xFFFF_0007);
+        assert_eq!(unknown.severity(), None);
+        assert_eq!(
+            unknown.to_string(),
+            "[Level 0x07] 0xFFFF: Unknown abnormality"
+        );
+
+        assert!(!HmsCode::default().is_abnormal());
+    }
+
+    #[test]
+    fn abnormal_slots_only() {
+        let hms_code = [0, 0x0104_0002, 0, 0, 0, 0, 0x0116_0004, 0];
+        let diagnostics: Vec<_> = decode_hms(&hms_code).collect();
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic {
+                    slot: 1,
+                    code: HmsCode(0x0104_0002)
+                },
+                Diagnostic {
+                    slot: 6,
+                    code: HmsCode(0x0116_0004)
+                },
+            ]
+        );
+
+        let state = LidarStateInfo {
+            hms_code: Some(hms_code),
+            ..Default::default()
+        };
+        assert_eq!(state.diagnostics().count(), 2);
+        assert_eq!(state.max_severity(), Some(Severity::Fatal));
+        assert_eq!(LidarStateInfo::default().max_severity(), None);
+    }
+
+    #[test]
+    fn status_slots_in_little_endian() {
+        let mut status_code = [0; 32];
+        status_code[8..12].copy_from_slice(&[0x01, 0x00, 0x05, 0x04]);
+        let slots = status_slots(&status_code);
+        assert_eq!(slots[2], HmsCode(0x0405_0001));
+        assert!(
+            slots
+                .iter()
+                .enumerate()
+                .all(|(slot, code)| slot == 2 || !code.is_abnormal())
+        );
+
+        let state = LidarStateInfo {
+            status_code: Some(status_code),
+            ..Default::default()
+        };
+        assert_eq!(
+            state.status_diagnostics().collect::<Vec<_>>(),
+            [Diagnostic {
+                slot: 2,
+                code: HmsCode(0x0405_0001)
+            }]
+        );
+    }
+}
@@ -1,71 +1,191 @@
 use std::{
     fmt::Display,
     io::{self, ErrorKind},
-    ops::Deref,
+    net::Ipv4Addr,
+    ops::{Deref, RangeInclusive},
 };
 
 use thiserror::Error;
-use zerocopy::{CastError, KnownLayout, SizeError, TryCastError, TryFromBytes};
+use zerocopy::{
+    CastError, ConvertError, IntoBytes, KnownLayout, SizeError, TryCastError, TryFromBytes,
+};
+
+use crate::types::{
+    ethernet::PointDataType,
+    key_value::ParamKey,
+    sdk_packet::{CommandID, ReturnCode},
+};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, Error)]
-#[error(transparent)]
-pub struct Error {
-    #[from]
-    source: std::io::Error,
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The packet, or a field of it, is shorter than its layout or its declared length.
+    #[error("Truncated packet: expected {expected} bytes, found {found}.")]
+    Truncated { expected: usize, found: usize },
+    /// An enum field of the packet holds an unknown discriminant,
+    /// `offset` is the byte offset of the field and `value` is read in little endian.
+    #[error("Invalid discriminant {value:#X} at byte offset {offset}.")]
+    InvalidDiscriminant { offset: usize, value: u32 },
+    /// The packet is not of the expected point data type.
+    #[error("Unexpected data type: {0}.")]
+    WrongDataType(PointDataType),
+    /// The frame is not of the expected command.
+    #[error("Unexpected command: expected {expected}, found {found}.")]
+    WrongCommand {
+        expected: CommandID,
+        found: CommandID,
+    },
+    /// The checksum of the received packet does not match its content.
+    #[error("CRC mismatch: expected {expected:#010X}, calculated {calculated:#010X}.")]
+    CrcMismatch { expected: u32, calculated: u32 },
+    /// Operation timeouts, same as [`LidarStatus::Timeout`](crate::types::LidarStatus::Timeout).
+    #[error("Operation timeouts after {attempts} attempts.")]
+    Timeout { attempts: u32 },
+    /// The lidar responses with a non-zero return code,
+    /// `error_key` is the key that failed, see also [`ParamKey`].
+    #[error("{cmd_id} failed: {ret_code}{}.", ErrorKey(*.error_key))]
+    CommandFailed {
+        cmd_id: CommandID,
+        ret_code: ReturnCode,
+        error_key: Option<u16>,
+    },
+    /// The frame to send is longer than `max` bytes,
+    /// the [max length](crate::types::sdk_packet::SdkFrame::MAX_LEN) of a frame or the size of the buffer.
+    #[error("Frame of {len} bytes exceeds the max length {max}.")]
+    FrameTooLong { len: usize, max: usize },
+    /// An argument is out of its valid range,
+    /// e.g. the `timeout_ms` of [`ControlPort::reboot`](crate::lidar_port::ControlPort::reboot).
+    #[error("{name} {value} out of range {range:?}.")]
+    OutOfRange {
+        name: &'static str,
+        value: u64,
+        range: RangeInclusive<u64>,
+    },
+    /// No local network interface is on the same subnet as the lidar.
+    #[error("No local IPv4 interface is on the same subnet as the lidar {0}.")]
+    UnreachableLidar(Ipv4Addr),
 }
 
-impl Error {
-    pub fn invalid_size(expect: usize, found: usize) -> Self {
-        io::Error::new(
-            ErrorKind::InvalidData,
-            format!("Invalid size: expected {expect}, found {found}."),
-        )
-        .into()
+impl From<Error> for std::io::Error {
+    fn from(value: Error) -> Self {
+        let kind = match value {
+            Error::Io(source) => return source,
+            Error::Truncated { .. }
+            | Error::InvalidDiscriminant { .. }
+            | Error::WrongDataType(_)
+            | Error::WrongCommand { .. }
+            | Error::CrcMismatch { .. } => ErrorKind::InvalidData,
+            Error::Timeout { .. } => ErrorKind::TimedOut,
+            Error::CommandFailed { .. } => ErrorKind::Other,
+            Error::FrameTooLong { .. } | Error::OutOfRange { .. } => ErrorKind::InvalidInput,
+            Error::UnreachableLidar(_) => ErrorKind::NotFound,
+        };
+        io::Error::new(kind, value)
     }
-    pub fn unknown_type(expect: impl Display, found: impl Display) -> Self {
-        io::Error::new(
-            ErrorKind::InvalidData,
-            format!("unknown_type: expected {expect}, found {found}."),
-        )
-        .into()
+}
+
+/// Displays the `error_key` of [`Error::CommandFailed`] by name if it is known.
+struct ErrorKey(Option<u16>);
+
+impl Display for ErrorKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(key) = self.0 else {
+            return Ok(());
+        };
+        match ParamKey::try_read_from_bytes(key.as_bytes()) {
+            Ok(param_key) => write!(f, ", key {param_key:?} ({key:#06X})"),
+            Err(_) => write!(f, ", key {key:#06X}"),
+        }
     }
 }
 
-impl From<Error> for std::io::Error {
-    fn from(value: Error) -> Self {
-        let Error { source } = value;
-        source
+/// An enum field of a packet layout, see [`EnumFields`].
+pub(crate) struct EnumField {
+    offset: usize,
+    size: usize,
+    is_valid: fn(&[u8]) -> bool,
+}
+
+impl EnumField {
+    pub(crate) const fn new<E: TryFromBytes>(offset: usize) -> Self {
+        Self {
+            offset,
+            size: size_of::<E>(),
+            is_valid: |bytes| E::try_read_from_bytes(bytes).is_ok(),
+        }
+    }
+}
+
+/// The packet layouts parsed by the crate, listing their enum fields
+/// to locate the [`InvalidDiscriminant`](Error::InvalidDiscriminant) of a failed parsing.
+pub(crate) trait EnumFields {
+    const ENUM_FIELDS: &[EnumField] = &[];
+}
+
+impl Error {
+    fn invalid_discriminant<T: EnumFields>(source: &[u8]) -> Self {
+        T::ENUM_FIELDS
+            .iter()
+            .find_map(|field| {
+                let bytes = source.get(field.offset..field.offset + field.size)?;
+                (!(field.is_valid)(bytes)).then(|| Self::InvalidDiscriminant {
+                    offset: field.offset,
+                    value: bytes
+                        .iter()
+                        .rev()
+                        .fold(0, |value, &byte| value << 8 | byte as u32),
+                })
+            })
+            .unwrap_or_else(|| {
+                io::Error::new(ErrorKind::InvalidData, "Invalid discriminant.").into()
+            })
     }
 }
 
 impl<Src, Dst> From<SizeError<Src, Dst>> for Error
 where
-    Src: Deref,
+    Src: Deref<Target = [u8]>,
     Dst: KnownLayout,
 {
     fn from(error: SizeError<Src, Dst>) -> Self {
-        io::Error::new(ErrorKind::InvalidData, error.to_string()).into()
+        Self::Truncated {
+            expected: size_of::<Dst>(),
+            found: error.into_src().len(),
+        }
     }
 }
 
 impl<Src, Dst> From<TryCastError<Src, Dst>> for Error
 where
-    Src: Deref,
-    Dst: TryFromBytes + KnownLayout + ?Sized,
+    Src: Deref<Target = [u8]>,
+    Dst: TryFromBytes + KnownLayout + EnumFields,
 {
     fn from(error: TryCastError<Src, Dst>) -> Self {
-        io::Error::new(ErrorKind::InvalidData, error.to_string()).into()
+        match error {
+            ConvertError::Alignment(error) => {
+                io::Error::new(ErrorKind::InvalidData, error.to_string()).into()
+            }
+            ConvertError::Size(error) => error.into(),
+            ConvertError::Validity(error) => Self::invalid_discriminant::<Dst>(&error.into_src()),
+        }
     }
 }
 
 impl<Src, Dst> From<CastError<Src, Dst>> for Error
 where
-    Src: Deref,
-    Dst: TryFromBytes + KnownLayout + ?Sized,
+    Src: Deref<Target = [u8]>,
+    Dst: TryFromBytes + KnownLayout,
 {
     fn from(error: CastError<Src, Dst>) -> Self {
-        io::Error::new(ErrorKind::InvalidData, error.to_string()).into()
+        match error {
+            ConvertError::Alignment(error) => {
+                io::Error::new(ErrorKind::InvalidData, error.to_string()).into()
+            }
+            ConvertError::Size(error) => error.into(),
+            ConvertError::Validity(never) => match never {},
+        }
     }
 }
@@ -11,13 +11,25 @@
 //! Once you have the device port instances, you can call [`next_packet_ref`](lidar_port::PointDataPort::next_packet_ref) method to
 //! receive the corresponding data packets.
 //!
+//! # Runtime
+//! The ports run on [`async-net`](https://docs.rs/async-net) by default, which works with any executor.
+//! Enable the `tokio` feature and disable the default features to run them on `tokio` instead, see also [`runtime`].
+//! On Linux, the `io-uring` feature adds an io_uring based point cloud port, see also `lidar_port::uring`.
+//! The [`blocking`] module provides the ports based on `std::net::UdpSocket` for the non-async code.
+//! With the default features disabled and no runtime enabled, only the [`blocking`] ports and the packet parsing in [`types`] are built.
+//!
 //! # Example
 //! See also [example](https://github.com/ZXY595/livox2-rs/tree/main/example)
 
+pub mod blocking;
 mod crc;
+pub mod diagnostics;
 pub mod error;
 pub mod lidar_port;
+pub mod nmea;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+pub mod runtime;
 mod seq;
 pub mod types;
 
-pub use error::Error;
+pub use error::{Error, Result};
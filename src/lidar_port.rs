@@ -4,13 +4,54 @@
 //! or using configuration helper functions.
 use std::net::Ipv4Addr;
 
+#[cfg(all(target_os = "linux", any(feature = "async-net", feature = "tokio")))]
+pub mod batch;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+pub mod command;
+pub mod control;
+mod crc_check;
 pub mod detection;
+pub mod discovery;
+pub mod frame;
 pub mod imu;
+pub mod interface;
 pub mod point_data;
+mod retry;
+pub mod state_info;
+pub(crate) mod stats;
+#[cfg(all(
+    target_os = "linux",
+    feature = "io-uring",
+    any(feature = "async-net", feature = "tokio")
+))]
+pub mod uring;
 
+#[cfg(all(target_os = "linux", any(feature = "async-net", feature = "tokio")))]
+pub use batch::PacketBatch;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+pub use command::CommandClient;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+pub use control::ControlPort;
+pub use crc_check::CrcCheck;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
 pub use detection::DetectionPort;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+pub use discovery::Discovery;
+pub use frame::{Frame, FrameAssembler, FrameSplit};
+#[cfg(any(feature = "async-net", feature = "tokio"))]
 pub use imu::ImuPort;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
 pub use point_data::PointDataPort;
+pub use retry::RetryPolicy;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+pub use state_info::StateInfoPort;
+pub use stats::PortStats;
+#[cfg(all(
+    target_os = "linux",
+    feature = "io-uring",
+    any(feature = "async-net", feature = "tokio")
+))]
+pub use uring::UringPointDataPort;
 
 #[derive(Debug, Clone)]
 pub struct IpConfig {
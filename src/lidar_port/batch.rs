@@ -0,0 +1,200 @@
+//! Batched receiving of the point cloud packets by `recvmmsg`, only available on Linux.
+use std::{
+    io,
+    os::fd::{AsRawFd, RawFd},
+    ptr,
+};
+
+use crate::runtime::{Runtime, UdpSocket};
+
+use super::{PointDataPort, point_data::PointPacketRef};
+
+/// A preallocated slab receiving up to [`capacity`](Self::capacity) datagrams by one `recvmmsg`.
+///
+/// See also [`PointDataPort::next_packet_batch`].
+pub struct PacketBatch {
+    slab: Box<[u8]>,
+    packet_size: usize,
+    /// Points into the `slab`, only kept alive for the `headers`.
+    _iovecs: Box<[libc::iovec]>,
+    /// Points into the `iovecs`, the received lengths are written back by the kernel.
+    headers: Box<[libc::mmsghdr]>,
+    len: usize,
+}
+
+// Safety: the raw pointers only point into the boxes owned by the batch,
+// which are never moved or reallocated.
+unsafe impl Send for PacketBatch {}
+unsafe impl Sync for PacketBatch {}
+
+impl PacketBatch {
+    /// Creates a batch of `capacity` buffers, each of `packet_size` bytes.
+    pub fn new(capacity: usize, packet_size: usize) -> Self {
+        let mut slab = vec![0; capacity * packet_size].into_boxed_slice();
+        let mut iovecs: Box<[libc::iovec]> = slab
+            .chunks_exact_mut(packet_size.max(1))
+            .take(capacity)
+            .map(|buffer| libc::iovec {
+                iov_base: buffer.as_mut_ptr().cast(),
+                iov_len: packet_size,
+            })
+            .collect();
+        let headers = iovecs
+            .iter_mut()
+            .map(|iovec| {
+                // Safety: all-zero is a valid `msghdr`.
+                let mut msg_hdr: libc::msghdr = unsafe { std::mem::zeroed() };
+                msg_hdr.msg_iov = iovec;
+                msg_hdr.msg_iovlen = 1;
+                libc::mmsghdr {
+                    msg_hdr,
+                    msg_len: 0,
+                }
+            })
+            .collect();
+        Self {
+            slab,
+            packet_size,
+            _iovecs: iovecs,
+            headers,
+            len: 0,
+        }
+    }
+
+    /// Max number of datagrams received at once.
+    pub fn capacity(&self) -> usize {
+        self.headers.len()
+    }
+
+    /// Number of datagrams received by the last `recvmmsg`.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the received datagrams.
+    pub fn packets(&self) -> impl ExactSizeIterator<Item = &[u8]> {
+        self.headers[..self.len]
+            .iter()
+            .zip(self.slab.chunks_exact(self.packet_size.max(1)))
+            .map(|(header, buffer)| &buffer[..(header.msg_len as usize).min(buffer.len())])
+    }
+
+    /// Parses the received datagrams as point cloud packets.
+    pub fn point_packets(
+        &self,
+    ) -> impl ExactSizeIterator<Item = Result<PointPacketRef<'_>, crate::Error>> {
+        self.packets().map(PointPacketRef::try_from_bytes)
+    }
+
+    /// Receives without blocking, returns [`WouldBlock`](io::ErrorKind::WouldBlock) if nothing is queued.
+    fn recv(&mut self, fd: RawFd) -> io::Result<usize> {
+        self.len = 0;
+        let vlen = self.headers.len().min(u32::MAX as usize) as libc::c_uint;
+        // Safety: the headers point to the valid iovecs and buffers of this batch.
+        let received = unsafe {
+            libc::recvmmsg(
+                fd,
+                self.headers.as_mut_ptr(),
+                vlen,
+                libc::MSG_DONTWAIT,
+                ptr::null_mut(),
+            )
+        };
+        if received < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        self.len = received as usize;
+        Ok(self.len)
+    }
+}
+
+impl<R: Runtime> PointDataPort<R>
+where
+    R::UdpSocket: AsRawFd,
+{
+    /// Receives as many queued packets as the `batch` can hold by one `recvmmsg`,
+    /// waiting until at least one packet arrives.
+    ///
+    /// Returns an iterator parsing the packets in the `batch` without allocating,
+    /// each packet may fail to parse on its own.
+    /// The [`CrcCheck`](super::CrcCheck) of the port is applied to each packet,
+    /// the packets dropped by it are skipped,
+    /// and the packets are counted in the [`stats`](Self::stats) as they are iterated.
+    pub async fn next_packet_batch<'a>(
+        &'a mut self,
+        batch: &'a mut PacketBatch,
+    ) -> crate::Result<impl Iterator<Item = Result<PointPacketRef<'a>, crate::Error>> + use<'a, R>>
+    {
+        let fd = self.socket.as_raw_fd();
+        self.socket.read_with(|| batch.recv(fd)).await?;
+
+        let batch: &'a PacketBatch = batch;
+        let Self {
+            crc_check, stats, ..
+        } = self;
+        let crc_check = *crc_check;
+        Ok(batch
+            .packets()
+            .filter_map(move |packet| match stats.accept(crc_check, packet) {
+                Ok(true) => Some(
+                    stats.record_parsed(PointPacketRef::try_from_bytes(packet), |packet| {
+                        packet.header
+                    }),
+                ),
+                Ok(false) => None,
+                Err(err) => Some(Err(err)),
+            }))
+    }
+}
+
+#[cfg(all(test, feature = "async-net"))]
+mod tests {
+    use std::net::UdpSocket as StdUdpSocket;
+
+    use super::*;
+
+    /// A packet of one cartesian high point.
+    fn packet(udp_cnt: u16) -> Vec<u8> {
+        const LEN: u16 = 36 + 14;
+        let mut packet = vec![0; LEN as usize];
+        packet[1..3].copy_from_slice(&LEN.to_le_bytes());
+        packet[5..7].copy_from_slice(&1u16.to_le_bytes());
+        packet[7..9].copy_from_slice(&udp_cnt.to_le_bytes());
+        packet[10] = 1;
+        packet
+    }
+
+    #[test]
+    fn receive_a_batch() {
+        smol::block_on(async {
+            let lidar = StdUdpSocket::bind("127.0.0.1:0").unwrap();
+            let mut port =
+                PointDataPort::new(([127, 0, 0, 1], 0), lidar.local_addr().unwrap(), 1380)
+                    .await
+                    .unwrap();
+            lidar.connect(port.socket.local_addr().unwrap()).unwrap();
+            for udp_cnt in 0..3 {
+                lidar.send(&packet(udp_cnt)).unwrap();
+            }
+            lidar.send(&[0; 10]).unwrap();
+
+            let mut batch = PacketBatch::new(8, 1380);
+            let mut received = Vec::new();
+            while received.len() < 4 {
+                received.extend(
+                    port.next_packet_batch(&mut batch)
+                        .await
+                        .unwrap()
+                        .map(|packet| packet.map(|packet| packet.header.udp_cnt).ok()),
+                );
+            }
+            assert_eq!(received, [Some(0), Some(1), Some(2), None]);
+            let stats = port.stats();
+            assert_eq!((stats.received, stats.parse_errors), (4, 1));
+        });
+    }
+}
@@ -0,0 +1,304 @@
+//! Request/response correlation of the sdk commands.
+//!
+//! Every request is tracked by its `(cmd_id, seq_num)` until the matching ACK arrives,
+//! so many commands can be in flight on the same socket concurrently.
+use std::{
+    collections::HashMap,
+    future::poll_fn,
+    io,
+    sync::{Mutex, MutexGuard, PoisonError},
+    task::{Poll, Waker},
+};
+
+use crate::{
+    runtime::{Runtime, UdpSocket},
+    types::sdk_packet::{CommandID, CommandType, SdkFrame, SdkPacketHeader, SendType},
+};
+
+#[cfg(feature = "async-net")]
+use crate::runtime::AsyncNet;
+
+use super::RetryPolicy;
+
+type PendingKey = (CommandID, u32);
+
+enum PendingAck {
+    Waiting(Option<Waker>),
+    /// The data segment of the ACK, received by another request.
+    Ready(Vec<u8>),
+}
+
+/// A command client on a connected socket.
+///
+/// The ACKs that don't match any outstanding request, e.g. a stale or duplicated ACK, are ignored.
+pub struct CommandClient<R: Runtime> {
+    socket: R::UdpSocket,
+    pending: Mutex<HashMap<PendingKey, PendingAck>>,
+    buffer_size: usize,
+    retry_policy: RetryPolicy,
+}
+
+#[cfg(feature = "async-net")]
+impl CommandClient<AsyncNet> {
+    /// Creates the client on [`AsyncNet`], see also [`new_with_runtime`](Self::new_with_runtime).
+    pub fn new(socket: async_net::UdpSocket, buffer_size: usize) -> Self {
+        Self::new_with_runtime(socket, buffer_size)
+    }
+}
+
+impl<R: Runtime> CommandClient<R> {
+    /// `socket` should be connected to the command port of the lidar,
+    /// `buffer_size` is the size of the receiving buffer of each request.
+    pub fn new_with_runtime(socket: R::UdpSocket, buffer_size: usize) -> Self {
+        Self {
+            socket,
+            pending: Mutex::default(),
+            buffer_size,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    pub fn socket(&self) -> &R::UdpSocket {
+        &self.socket
+    }
+
+    /// Sends a request and waits for the ACK with the same `cmd_id` and `seq_num`,
+    /// returns the data segment of the ACK.
+    ///
+    /// The request is resent with the same `seq_num` according to the [`RetryPolicy`],
+    /// so a late ACK of the previous attempt is also accepted.
+    ///
+    /// This method is cancel safe, the request will be forgotten if the future is dropped.
+    pub async fn request(&self, cmd_id: CommandID, data: &[u8]) -> crate::Result<Vec<u8>> {
+        let seq_num = crate::seq::next_seq();
+        let frame = SdkFrame::encode(seq_num, cmd_id, CommandType::Cmd, SendType::HostSend, data)?;
+        let key = (cmd_id, seq_num);
+        let _pending = PendingGuard::register(self, key);
+
+        let mut buffer = vec![0; self.buffer_size];
+        self.retry_policy
+            .run::<R, _>(async || {
+                self.socket.send(&frame).await?;
+                loop {
+                    let ack = futures_lite::future::or(
+                        self.wait_ack(key),
+                        self.recv_ack(key, &mut buffer),
+                    )
+                    .await?;
+                    if let Some(ack) = ack {
+                        return Ok(ack);
+                    }
+                }
+            })
+            .await
+    }
+
+    /// Waits until the ACK of `key` is received by another request.
+    async fn wait_ack(&self, key: PendingKey) -> Result<Option<Vec<u8>>, io::Error> {
+        poll_fn(|cx| {
+            let mut pending = self.lock_pending();
+            match pending.get_mut(&key) {
+                Some(PendingAck::Ready(_)) => match pending.remove(&key) {
+                    Some(PendingAck::Ready(ack)) => Poll::Ready(Ok(Some(ack))),
+                    _ => unreachable!(),
+                },
+                Some(PendingAck::Waiting(waker)) => {
+                    *waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+                None => Poll::Pending,
+            }
+        })
+        .await
+    }
+
+    /// Receives an ACK, returns it if it belongs to `key`,
+    /// otherwise hands it over to the corresponding outstanding request.
+    async fn recv_ack(
+        &self,
+        key: PendingKey,
+        buffer: &mut [u8],
+    ) -> Result<Option<Vec<u8>>, io::Error> {
+        let len = self.socket.recv(buffer).await?;
+        let Some((ack_key, data)) = parse_ack(&buffer[..len]) else {
+            return Ok(None);
+        };
+        if ack_key == key {
+            return Ok(Some(data.to_vec()));
+        }
+
+        let mut pending = self.lock_pending();
+        if let Some(slot @ PendingAck::Waiting(_)) = pending.get_mut(&ack_key)
+            && let PendingAck::Waiting(Some(waker)) =
+                std::mem::replace(slot, PendingAck::Ready(data.to_vec()))
+        {
+            waker.wake();
+        }
+        Ok(None)
+    }
+
+    fn lock_pending(&self) -> MutexGuard<'_, HashMap<PendingKey, PendingAck>> {
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Returns the key and the data segment if the frame is a valid ACK.
+fn parse_ack(frame: &[u8]) -> Option<(PendingKey, &[u8])> {
+    let (header, data) = SdkPacketHeader::try_ref_from_frame(frame).ok()?;
+    if { header.cmd_type } != CommandType::Ack {
+        return None;
+    }
+    Some(((header.cmd_id, header.seq_num), data))
+}
+
+/// Removes the outstanding request when dropped.
+struct PendingGuard<'a, R: Runtime> {
+    client: &'a CommandClient<R>,
+    key: PendingKey,
+}
+
+impl<'a, R: Runtime> PendingGuard<'a, R> {
+    fn register(client: &'a CommandClient<R>, key: PendingKey) -> Self {
+        client.lock_pending().insert(key, PendingAck::Waiting(None));
+        Self { client, key }
+    }
+}
+
+impl<R: Runtime> Drop for PendingGuard<'_, R> {
+    fn drop(&mut self) {
+        self.client.lock_pending().remove(&self.key);
+    }
+}
+
+#[cfg(all(test, feature = "async-net"))]
+mod tests {
+    use std::{net::UdpSocket as StdUdpSocket, thread, time::Duration};
+
+    use super::*;
+    use crate::runtime::AsyncNet;
+
+    /// A fake lidar replying the requests in the order given by `reply`.
+    fn fake_lidar(
+        requests: usize,
+        reply: impl FnOnce(&StdUdpSocket, Vec<Vec<u8>>) + Send + 'static,
+    ) -> (StdUdpSocket, thread::JoinHandle<Vec<Vec<u8>>>) {
+        let lidar = StdUdpSocket::bind("127.0.0.1:0").unwrap();
+        let socket = lidar.try_clone().unwrap();
+        let handle = thread::spawn(move || {
+            let mut buffer = [0; SdkFrame::MAX_LEN];
+            let frames: Vec<_> = (0..requests)
+                .map(|_| {
+                    let (len, peer) = socket.recv_from(&mut buffer).unwrap();
+                    socket.connect(peer).unwrap();
+                    buffer[..len].to_vec()
+                })
+                .collect();
+            reply(&socket, frames.clone());
+            frames
+        });
+        (lidar, handle)
+    }
+
+    fn ack(request: &[u8], data: &[u8]) -> Vec<u8> {
+        SdkFrame::encode_reply(SdkFrame::parse(request).unwrap().header, data).unwrap()
+    }
+
+    async fn client(lidar: &StdUdpSocket, timeout: Duration) -> CommandClient<AsyncNet> {
+        let local_addr = ([127, 0, 0, 1], 0).into();
+        let socket = crate::runtime::connect::<AsyncNet>(local_addr, lidar.local_addr().unwrap())
+            .await
+            .unwrap();
+        let mut client = CommandClient::new_with_runtime(socket, SdkFrame::MAX_LEN);
+        client.set_retry_policy(RetryPolicy::no_retry(timeout));
+        client
+    }
+
+    #[test]
+    fn out_of_order_acks() {
+        let (lidar, handle) = fake_lidar(2, |socket, requests| {
+            for request in requests.iter().rev() {
+                let data = SdkFrame::parse(request).unwrap().data;
+                socket.send(&ack(request, data)).unwrap();
+            }
+        });
+        smol::block_on(async {
+            let client = client(&lidar, Duration::from_secs(1)).await;
+            let (first, second) = futures_lite::future::zip(
+                client.request(CommandID::InquireLidarInfo, &[1]),
+                client.request(CommandID::InquireLidarInfo, &[2]),
+            )
+            .await;
+            assert_eq!(first.unwrap(), [1]);
+            assert_eq!(second.unwrap(), [2]);
+            assert!(client.lock_pending().is_empty());
+        });
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn stale_and_duplicate_acks() {
+        let (lidar, handle) = fake_lidar(1, |socket, requests| {
+            let request = SdkFrame::parse(&requests[0]).unwrap().header;
+            let stale = SdkFrame::encode(
+                request.seq_num.wrapping_sub(1),
+                request.cmd_id,
+                CommandType::Ack,
+                SendType::LidarSend,
+                &[0xFF],
+            )
+            .unwrap();
+            let other_command = SdkFrame::encode(
+                request.seq_num,
+                CommandID::RestoreFactorySettings,
+                CommandType::Ack,
+                SendType::LidarSend,
+                &[0xFF],
+            )
+            .unwrap();
+            let mut corrupted = ack(&requests[0], &[0xFF]);
+            *corrupted.last_mut().unwrap() ^= 1;
+            let ack = ack(&requests[0], &[0]);
+
+            for frame in [&stale, &other_command, &requests[0], &corrupted, &ack, &ack] {
+                socket.send(frame).unwrap();
+            }
+        });
+        smol::block_on(async {
+            let mut client = client(&lidar, Duration::from_secs(1)).await;
+            let ack = client.request(CommandID::RequestRebootDevice, &[]).await;
+            assert_eq!(ack.unwrap(), [0]);
+
+            // The duplicate ACK of the last request is ignored.
+            client.set_retry_policy(RetryPolicy::no_retry(Duration::from_millis(100)));
+            let next = client.request(CommandID::RequestRebootDevice, &[]).await;
+            assert!(matches!(next, Err(crate::Error::Timeout { attempts: 1 })));
+        });
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn retry_with_the_same_seq_num() {
+        let (lidar, handle) = fake_lidar(2, |socket, requests| {
+            socket.send(&ack(&requests[1], &[0])).unwrap();
+        });
+        smol::block_on(async {
+            let mut client = client(&lidar, Duration::ZERO).await;
+            client.set_retry_policy(RetryPolicy {
+                retries: 1,
+                ..RetryPolicy::no_retry(Duration::from_millis(100))
+            });
+            let ack = client.request(CommandID::RequestRebootDevice, &[]).await;
+            assert_eq!(ack.unwrap(), [0]);
+        });
+        let requests = handle.join().unwrap();
+        assert_eq!(requests[0], requests[1]);
+    }
+}
@@ -0,0 +1,352 @@
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use std::{
+    io,
+    net::{IpAddr, SocketAddr},
+    ops::RangeInclusive,
+    time::{Duration, Instant},
+};
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use futures_lite::{AsyncBufReadExt, AsyncRead, io::BufReader};
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use zerocopy::IntoBytes;
+use zerocopy::TryFromBytes;
+
+#[cfg(feature = "async-net")]
+use crate::runtime::AsyncNet;
+use crate::types::{key_value::KeyValueIter, sdk_packet::InquireLidarInfoAck};
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use crate::{
+    nmea::Rmc,
+    runtime::{self, Runtime, UdpSocket},
+    types::{
+        WorkMode,
+        key_value::{ConfigParam, KeyValueList, KeyValueListHeader, ParamKey},
+        sdk_packet::{CommandID, ConfigParamAck, ControlAck, GpsTimestamp, SdkFrame},
+        state_info::LidarStateInfo,
+    },
+};
+
+use super::SocketPortConfig;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use super::{Discovery, RetryPolicy, command::CommandClient};
+
+/// The command port of the lidar, used to configure and control the device.
+///
+/// All the commands take `&self`, so they can be sent concurrently.
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+pub struct ControlPort<R: Runtime> {
+    client: CommandClient<R>,
+}
+
+#[cfg(feature = "async-net")]
+impl ControlPort<AsyncNet> {
+    /// Creates the port on [`AsyncNet`], see also [`new_with_runtime`](Self::new_with_runtime).
+    pub async fn new(
+        local_addr: impl Into<SocketAddr>,
+        lidar_addr: impl Into<SocketAddr>,
+        buffer_init_size: usize,
+    ) -> crate::Result<Self> {
+        Self::new_with_runtime(local_addr, lidar_addr, buffer_init_size).await
+    }
+}
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+impl<R: Runtime> ControlPort<R> {
+    /// Max length of an sdk frame.
+    const DEFAULT_BUFFER_INIT_SIZE: usize = 1400;
+
+    /// The accepted range of the reboot delay, in milliseconds.
+    pub const REBOOT_TIMEOUT_RANGE: RangeInclusive<u16> = 100..=2000;
+
+    /// How long each discovery round lasts while waiting for the lidar to reboot.
+    const REBOOT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+    pub async fn new_with_runtime(
+        local_addr: impl Into<SocketAddr>,
+        lidar_addr: impl Into<SocketAddr>,
+        buffer_init_size: usize,
+    ) -> crate::Result<Self> {
+        let socket = runtime::connect::<R>(local_addr.into(), lidar_addr.into()).await?;
+
+        Ok(Self {
+            client: CommandClient::new_with_runtime(socket, buffer_init_size),
+        })
+    }
+
+    /// The underlying client, which can be used to send any other command.
+    pub fn client(&self) -> &CommandClient<R> {
+        &self.client
+    }
+
+    /// Sets the timeout and retry policy of all the commands, see also [`RetryPolicy::default`].
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.client.set_retry_policy(retry_policy);
+        self
+    }
+
+    /// Sends a [`ConfigParamInfo`](CommandID::ConfigParamInfo) request with the given parameters.
+    ///
+    /// # Error
+    ///
+    /// Returns [`Error::CommandFailed`](crate::Error::CommandFailed) naming the failed key
+    /// if the parameters have not been applied,
+    /// or [`Error::FrameTooLong`](crate::Error::FrameTooLong) if they don't fit in a frame.
+    pub async fn config_params(&self, params: &[ConfigParam]) -> crate::Result<()> {
+        let mut key_values = KeyValueList::new();
+        key_values.try_extend(params)?;
+        self.config_key_values(&key_values).await
+    }
+
+    /// Same as [`config_params`](Self::config_params), but sends a pre-built key-value list.
+    pub async fn config_key_values(&self, key_values: &KeyValueList) -> crate::Result<()> {
+        let data = self
+            .client
+            .request(CommandID::ConfigParamInfo, key_values.as_bytes())
+            .await?;
+        let (ack, _) = ConfigParamAck::try_ref_from_prefix(&data)?;
+        ack.check()
+    }
+
+    /// Sends an [`InquireLidarInfo`](CommandID::InquireLidarInfo) request for the given keys,
+    /// returns the data segment of the ACK, which can be parsed by [`InquireLidarInfoAckRef`].
+    ///
+    /// see also [`ParamKey::ALL`].
+    ///
+    /// # Error
+    ///
+    /// Returns [`Error::FrameTooLong`](crate::Error::FrameTooLong) if the keys don't fit in a frame.
+    pub async fn inquire_raw(&self, keys: &[ParamKey]) -> crate::Result<Vec<u8>> {
+        let request_len = size_of::<KeyValueListHeader>() + size_of_val(keys);
+        let len = SdkFrame::frame_len(request_len);
+        if len > SdkFrame::MAX_LEN {
+            return Err(crate::Error::FrameTooLong {
+                len,
+                max: SdkFrame::MAX_LEN,
+            });
+        }
+        let list_header = KeyValueListHeader {
+            key_num: keys.len() as u16,
+            rsvd: 0,
+        };
+        let mut request = Vec::with_capacity(request_len);
+        request.extend_from_slice(list_header.as_bytes());
+        request.extend_from_slice(keys.as_bytes());
+
+        self.client
+            .request(CommandID::InquireLidarInfo, &request)
+            .await
+    }
+
+    /// Inquires the given keys and decodes them into a [`LidarStateInfo`].
+    ///
+    /// # Error
+    ///
+    /// Fail if the lidar responses with a non-zero return code.
+    pub async fn inquire(&self, keys: &[ParamKey]) -> crate::Result<LidarStateInfo> {
+        let data = self.inquire_raw(keys).await?;
+        let ack = InquireLidarInfoAckRef::try_from_bytes(&data)?;
+        ack.header.check()?;
+        LidarStateInfo::from_key_values(ack.key_values())
+    }
+
+    /// Sends a [`RequestRebootDevice`](CommandID::RequestRebootDevice) request,
+    /// the lidar reboots `timeout_ms` after acknowledging it.
+    ///
+    /// # Error
+    ///
+    /// Returns [`Error::OutOfRange`](crate::Error::OutOfRange) if `timeout_ms` is out of
+    /// [`REBOOT_TIMEOUT_RANGE`](Self::REBOOT_TIMEOUT_RANGE).
+    pub async fn reboot(&self, timeout_ms: u16) -> crate::Result<()> {
+        if !Self::REBOOT_TIMEOUT_RANGE.contains(&timeout_ms) {
+            return Err(crate::Error::OutOfRange {
+                name: "reboot timeout_ms",
+                value: timeout_ms as u64,
+                range: *Self::REBOOT_TIMEOUT_RANGE.start() as u64
+                    ..=*Self::REBOOT_TIMEOUT_RANGE.end() as u64,
+            });
+        }
+        self.control(CommandID::RequestRebootDevice, &timeout_ms.to_le_bytes())
+            .await
+    }
+
+    /// Same as [`reboot`](Self::reboot), but waits until the lidar shows up again in the `discovery`
+    /// and reports [`WorkMode::Normal`], or the `wait` passes.
+    ///
+    /// # Error
+    ///
+    /// Returns [`Error::Timeout`](crate::Error::Timeout) counting the discovery rounds
+    /// if the lidar is not back in time.
+    pub async fn reboot_and_wait(
+        &self,
+        timeout_ms: u16,
+        discovery: &mut Discovery<R>,
+        wait: Duration,
+    ) -> crate::Result<()> {
+        let deadline = Instant::now() + wait;
+        let IpAddr::V4(lidar_ip) = self.client.socket().peer_addr()?.ip() else {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "IPv6 lidar.").into());
+        };
+        self.reboot(timeout_ms).await?;
+        // Don't catch the lidar before it goes down.
+        R::sleep(Duration::from_millis(timeout_ms as u64)).await;
+
+        let mut attempts = 0;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            attempts += 1;
+            let devices = discovery
+                .discover(Self::REBOOT_POLL_INTERVAL.min(remaining))
+                .await?;
+            if devices.iter().all(|device| device.lidar_ip != lidar_ip) {
+                continue;
+            }
+            // The command port may not respond while the lidar is still booting.
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let inquire = self.inquire(&[ParamKey::CurWorkState]);
+            if let Some(Ok(state)) = runtime::timeout::<R, _>(remaining, inquire).await
+                && state.cur_work_state == Some(WorkMode::Normal as u8)
+            {
+                return Ok(());
+            }
+        }
+        Err(crate::Error::Timeout { attempts })
+    }
+
+    /// Sends a [`RestoreFactorySettings`](CommandID::RestoreFactorySettings) request.
+    pub async fn restore_factory_settings(&self) -> crate::Result<()> {
+        self.control(CommandID::RestoreFactorySettings, &[]).await
+    }
+
+    /// Sends a [`SetGpsTimestamp`](CommandID::SetGpsTimestamp) request,
+    /// the time is taken as the time of the last PPS edge received by the lidar.
+    pub async fn set_gps_timestamp(&self, timestamp: GpsTimestamp<'_>) -> crate::Result<()> {
+        self.control(CommandID::SetGpsTimestamp, &timestamp.to_bytes())
+            .await
+    }
+
+    /// Reads the NMEA sentences from the `reader`, e.g. a GPS receiver on a serial port,
+    /// and forwards each valid [`Rmc`] at a whole second to the lidar by
+    /// [`set_gps_timestamp`](Self::set_gps_timestamp), until the `reader` reaches EOF.
+    ///
+    /// With the PPS signal wired to the lidar, the point cloud timestamps switch to
+    /// [`TimestampType::Gps`](crate::types::ethernet::TimestampType::Gps).
+    /// The other sentences, and the ones that are not newer than the last forwarded one, are skipped.
+    ///
+    /// # Error
+    ///
+    /// Fail if the `reader` fails, or the lidar rejects a timestamp.
+    pub async fn forward_nmea(&self, reader: impl AsyncRead + Unpin) -> crate::Result<()> {
+        let mut reader = BufReader::new(reader);
+        let mut line = Vec::new();
+        let mut last_utc_nanos = None;
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line).await? == 0 {
+                return Ok(());
+            }
+            let Some(rmc) = std::str::from_utf8(&line).ok().and_then(Rmc::parse) else {
+                continue;
+            };
+            if !rmc.is_pps_aligned() || last_utc_nanos >= Some(rmc.utc_nanos()) {
+                continue;
+            }
+            self.set_gps_timestamp(GpsTimestamp::Rmc(rmc.sentence()))
+                .await?;
+            last_utc_nanos = Some(rmc.utc_nanos());
+        }
+    }
+
+    /// Sends a control command, whose ACK is a [`ControlAck`].
+    async fn control(&self, cmd_id: CommandID, data: &[u8]) -> crate::Result<()> {
+        let data = self.client.request(cmd_id, data).await?;
+        let (ack, _) = ControlAck::try_ref_from_prefix(&data)?;
+        ack.check(cmd_id)
+    }
+}
+
+impl SocketPortConfig {
+    /// The `local` port follows the default host command port of the protocol.
+    pub const fn new_control_port_config() -> Self {
+        Self {
+            local: 56101,
+            lidar: 56100,
+        }
+    }
+}
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+impl super::IpConfig {
+    #[cfg(feature = "async-net")]
+    pub async fn new_control_port(
+        &self,
+        control_port: &SocketPortConfig,
+        buffer_init_size: usize,
+    ) -> crate::Result<ControlPort<AsyncNet>> {
+        self.new_control_port_with_runtime(control_port, buffer_init_size)
+            .await
+    }
+    #[cfg(feature = "async-net")]
+    pub async fn new_default_control_port(&self) -> crate::Result<ControlPort<AsyncNet>> {
+        self.new_default_control_port_with_runtime().await
+    }
+
+    /// Same as [`new_control_port`](Self::new_control_port), but on the given [`Runtime`].
+    pub async fn new_control_port_with_runtime<R: Runtime>(
+        &self,
+        control_port: &SocketPortConfig,
+        buffer_init_size: usize,
+    ) -> crate::Result<ControlPort<R>> {
+        ControlPort::new_with_runtime(
+            (self.local, control_port.local),
+            (self.lidar, control_port.lidar),
+            buffer_init_size,
+        )
+        .await
+    }
+    pub async fn new_default_control_port_with_runtime<R: Runtime>(
+        &self,
+    ) -> crate::Result<ControlPort<R>> {
+        self.new_control_port_with_runtime(
+            &SocketPortConfig::new_control_port_config(),
+            ControlPort::<R>::DEFAULT_BUFFER_INIT_SIZE,
+        )
+        .await
+    }
+}
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+impl super::LidarPortConfig {
+    #[cfg(feature = "async-net")]
+    pub async fn new_control_port(&self) -> crate::Result<ControlPort<AsyncNet>> {
+        self.new_control_port_with_runtime().await
+    }
+
+    /// Same as [`new_control_port`](Self::new_control_port), but on the given [`Runtime`].
+    pub async fn new_control_port_with_runtime<R: Runtime>(&self) -> crate::Result<ControlPort<R>> {
+        self.ip
+            .new_control_port_with_runtime(&self.port, self.buffer_init_size)
+            .await
+    }
+}
+
+#[derive(Debug)]
+pub struct InquireLidarInfoAckRef<'a> {
+    pub header: &'a InquireLidarInfoAck,
+    /// The key-value items following the header.
+    pub data: &'a [u8],
+}
+
+impl<'a> InquireLidarInfoAckRef<'a> {
+    pub fn try_from_bytes(source: &'a [u8]) -> Result<Self, crate::Error> {
+        let (header, data) = InquireLidarInfoAck::try_ref_from_prefix(source)?;
+        Ok(Self { header, data })
+    }
+
+    pub fn key_values(&self) -> KeyValueIter<'a> {
+        KeyValueIter::new(self.header.key_num, self.data)
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{Error, types::ethernet::EthernetPacketHeader};
+
+/// Whether and how the [`crc32`](EthernetPacketHeader::crc32) of the received packets is verified.
+///
+/// The failures are counted by the ports in any verifying mode, which helps to detect bad cabling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CrcCheck {
+    /// The packets are not verified.
+    #[default]
+    Off,
+    /// The mismatched packets, and the ones too short to be verified, are dropped silently,
+    /// and the port waits for the next one.
+    VerifyAndDrop,
+    /// The mismatched packets are reported as [`Error::CrcMismatch`],
+    /// and the ones too short to be verified as the parsing error.
+    VerifyAndReport,
+}
+
+impl CrcCheck {
+    /// Returns `false` if the packet should be dropped, the mismatched packets are counted in `crc_failures`,
+    /// and the ones too short to be verified in `parse_errors`.
+    pub(crate) fn verify(
+        self,
+        packet: &[u8],
+        crc_failures: &mut u64,
+        parse_errors: &mut u64,
+    ) -> crate::Result<bool> {
+        if let Self::Off = self {
+            return Ok(true);
+        }
+        let Err(err) = EthernetPacketHeader::verify_crc32(packet) else {
+            return Ok(true);
+        };
+        match err {
+            Error::CrcMismatch { .. } => *crc_failures += 1,
+            _ => *parse_errors += 1,
+        }
+        match self {
+            Self::VerifyAndReport => Err(err),
+            _ => Ok(false),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "async-net"))]
+mod tests {
+    use std::net::UdpSocket;
+
+    use super::*;
+    use crate::{lidar_port::PointDataPort, runtime::AsyncNet};
+
+    /// A packet of one cartesian high point with a valid crc32.
+    fn packet(udp_cnt: u16) -> Vec<u8> {
+        const LEN: u16 = 36 + 14;
+        let mut packet = vec![0; LEN as usize];
+        packet[1..3].copy_from_slice(&LEN.to_le_bytes());
+        packet[5..7].copy_from_slice(&1u16.to_le_bytes());
+        packet[7..9].copy_from_slice(&udp_cnt.to_le_bytes());
+        packet[10] = 1;
+        let crc32 = crate::crc::CRC32.checksum(&packet[28..]);
+        packet[24..28].copy_from_slice(&crc32.to_le_bytes());
+        packet
+    }
+
+    async fn loopback(crc_check: CrcCheck) -> (PointDataPort<AsyncNet>, UdpSocket) {
+        let lidar = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = PointDataPort::new(([127, 0, 0, 1], 0), lidar.local_addr().unwrap(), 1380)
+            .await
+            .unwrap()
+            .with_crc_check(crc_check);
+        lidar.connect(port.socket.local_addr().unwrap()).unwrap();
+        (port, lidar)
+    }
+
+    #[test]
+    fn drop_short_and_mismatched() {
+        smol::block_on(async {
+            let (mut port, lidar) = loopback(CrcCheck::VerifyAndDrop).await;
+            let mut mismatched = packet(1);
+            mismatched[40] ^= 1;
+            lidar.send(&[0; 10]).unwrap();
+            lidar.send(&mismatched).unwrap();
+            lidar.send(&packet(2)).unwrap();
+
+            let packet = port.next_packet_ref().await.unwrap();
+            assert_eq!({ packet.header.udp_cnt }, 2);
+            let stats = port.stats();
+            assert_eq!(
+                (stats.received, stats.crc_failures, stats.parse_errors),
+                (3, 1, 1)
+            );
+        });
+    }
+
+    #[test]
+    fn report_short() {
+        smol::block_on(async {
+            let (mut port, lidar) = loopback(CrcCheck::VerifyAndReport).await;
+            lidar.send(&[0; 10]).unwrap();
+            lidar.send(&packet(1)).unwrap();
+
+            assert!(port.next_packet_ref().await.is_err());
+            let packet = port.next_packet_ref().await.unwrap();
+            assert_eq!({ packet.header.udp_cnt }, 1);
+            let stats = port.stats();
+            assert_eq!((stats.crc_failures, stats.parse_errors), (0, 1));
+        });
+    }
+}
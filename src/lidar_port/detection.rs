@@ -1,44 +1,68 @@
-use std::{
-    io::{self, Write},
-    net::Ipv4Addr,
-};
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use std::net::{Ipv4Addr, SocketAddr};
 
-use async_net::UdpSocket;
 use zerocopy::{Immutable, IntoBytes, TryFromBytes};
 
-use crate::types::sdk_packet::{CommandID, CommandType, QueryDeviceTypeAck, SdkPacketHeader, SendType};
+#[cfg(feature = "async-net")]
+use crate::runtime::AsyncNet;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use crate::runtime::{Runtime, UdpSocket};
+use crate::types::sdk_packet::{
+    CommandID, CommandType, QueryDeviceTypeAck, SdkPacketHeader, SendType,
+};
 
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use super::RetryPolicy;
 use super::SocketPortConfig;
 
-pub struct DetectionPort {
-    socket: UdpSocket,
-    broadcast_socket: UdpSocket,
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+pub struct DetectionPort<R: Runtime> {
+    socket: R::UdpSocket,
+    broadcast_socket: R::UdpSocket,
     buffer: Vec<u8>,
+    retry_policy: RetryPolicy,
+}
+
+#[cfg(feature = "async-net")]
+impl DetectionPort<AsyncNet> {
+    /// Creates the port on [`AsyncNet`], see also [`new_with_runtime`](Self::new_with_runtime).
+    pub async fn new(
+        local_ip: impl Into<Ipv4Addr>,
+        lidar_ip: impl Into<Ipv4Addr>,
+        detection_port: u16,
+        buffer_init_size: usize,
+    ) -> crate::Result<Self> {
+        Self::new_with_runtime(local_ip, lidar_ip, detection_port, buffer_init_size).await
+    }
 }
 
-impl DetectionPort {
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+impl<R: Runtime> DetectionPort<R> {
     const DEFAULT_BUFFER_INIT_SIZE: usize = 48;
 
-    pub async fn new(
+    pub async fn new_with_runtime(
         local_ip: impl Into<Ipv4Addr>,
         lidar_ip: impl Into<Ipv4Addr>,
         detection_port: u16,
         buffer_init_size: usize,
-    ) -> Result<Self, io::Error> {
+    ) -> crate::Result<Self> {
         let local_ip = local_ip.into();
         let lidar_ip = lidar_ip.into();
 
         let broadcast_socket =
-            UdpSocket::bind((Ipv4Addr::new(255, 255, 255, 255), detection_port)).await?;
+            R::UdpSocket::bind(SocketAddr::from((Ipv4Addr::BROADCAST, detection_port))).await?;
 
-        let socket = UdpSocket::bind((local_ip, detection_port)).await?;
+        let socket = R::UdpSocket::bind(SocketAddr::from((local_ip, detection_port))).await?;
         socket.set_broadcast(true)?;
-        socket.connect((lidar_ip, detection_port)).await?;
+        socket
+            .connect(SocketAddr::from((lidar_ip, detection_port)))
+            .await?;
 
         Ok(Self {
             socket,
             broadcast_socket,
             buffer: vec![0; buffer_init_size],
+            retry_policy: RetryPolicy::default(),
         })
     }
 }
@@ -54,67 +78,113 @@ impl SocketPortConfig {
     }
 }
 
+#[cfg(any(feature = "async-net", feature = "tokio"))]
 impl super::IpConfig {
+    #[cfg(feature = "async-net")]
     pub async fn new_detection_port(
         &self,
-        imu_port: u16,
+        detection_port: u16,
         buffer_init_size: usize,
-    ) -> Result<DetectionPort, io::Error> {
-        DetectionPort::new(self.local, self.lidar, imu_port, buffer_init_size).await
+    ) -> crate::Result<DetectionPort<AsyncNet>> {
+        self.new_detection_port_with_runtime(detection_port, buffer_init_size)
+            .await
+    }
+    #[cfg(feature = "async-net")]
+    pub async fn new_default_detection_port(&self) -> crate::Result<DetectionPort<AsyncNet>> {
+        self.new_default_detection_port_with_runtime().await
     }
-    pub async fn new_default_detection_port(&self) -> Result<DetectionPort, io::Error> {
-        self.new_detection_port(
+
+    /// Same as [`new_detection_port`](Self::new_detection_port), but on the given [`Runtime`].
+    pub async fn new_detection_port_with_runtime<R: Runtime>(
+        &self,
+        detection_port: u16,
+        buffer_init_size: usize,
+    ) -> crate::Result<DetectionPort<R>> {
+        DetectionPort::new_with_runtime(self.local, self.lidar, detection_port, buffer_init_size)
+            .await
+    }
+    pub async fn new_default_detection_port_with_runtime<R: Runtime>(
+        &self,
+    ) -> crate::Result<DetectionPort<R>> {
+        self.new_detection_port_with_runtime(
             SocketPortConfig::DEFAULT_DETECTION_PORT,
-            DetectionPort::DEFAULT_BUFFER_INIT_SIZE,
+            DetectionPort::<R>::DEFAULT_BUFFER_INIT_SIZE,
         )
         .await
     }
 }
 
+#[cfg(any(feature = "async-net", feature = "tokio"))]
 impl super::LidarPortConfig {
-    pub async fn new_detection_port(&self) -> Result<DetectionPort, io::Error> {
+    #[cfg(feature = "async-net")]
+    pub async fn new_detection_port(&self) -> crate::Result<DetectionPort<AsyncNet>> {
+        self.new_detection_port_with_runtime().await
+    }
+
+    /// Same as [`new_detection_port`](Self::new_detection_port), but on the given [`Runtime`].
+    pub async fn new_detection_port_with_runtime<R: Runtime>(
+        &self,
+    ) -> crate::Result<DetectionPort<R>> {
         self.ip
-            .new_detection_port(self.port.lidar, self.buffer_init_size)
+            .new_detection_port_with_runtime(self.port.lidar, self.buffer_init_size)
             .await
     }
 }
 
-impl DetectionPort {
-    pub async fn next_packet_ref(&mut self) -> Result<LidarSearchAckRef<'_>, io::Error> {
-        let buf = &mut self.buffer;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+impl<R: Runtime> DetectionPort<R> {
+    /// Sets the timeout and retry policy of the detection, see also [`RetryPolicy::default`].
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
 
+    /// # Error
+    ///
+    /// Returns [`Error::Timeout`](crate::Error::Timeout) if the lidar does not response in time.
+    pub async fn next_packet_ref(&mut self) -> crate::Result<LidarSearchAckRef<'_>> {
         let packet = LidarSearchCmdPacket::new(CommandType::Cmd, SendType::HostSend);
-
-        let len = buf.write(packet.as_bytes())?;
-
-        self.socket.send(&buf[..len]).await?;
+        let seq_num = packet.header.seq_num;
         let dst_addr = self.socket.peer_addr()?;
 
-        // TODO: use timeout
-        let len = loop {
-            let (len, src) = self.broadcast_socket.recv_from(buf).await?;
-            if src == dst_addr {
-                break len;
-            }
-        };
-
-        LidarSearchAckRef::try_from_bytes(&buf[..len]).map_err(From::from)
+        let Self {
+            socket,
+            broadcast_socket,
+            buffer,
+            retry_policy,
+        } = self;
+        let len = retry_policy
+            .run::<R, _>(async || {
+                socket.send(packet.as_bytes()).await?;
+                loop {
+                    let (len, src) = broadcast_socket.recv_from(buffer).await?;
+                    let is_ack = SdkPacketHeader::try_ref_from_frame(&buffer[..len]).is_ok_and(
+                        |(header, _)| {
+                            let (cmd_type, ack_seq_num) = (header.cmd_type, header.seq_num);
+                            cmd_type == CommandType::Ack && ack_seq_num == seq_num
+                        },
+                    );
+                    if src == dst_addr && is_ack {
+                        return Ok(len);
+                    }
+                }
+            })
+            .await?;
+
+        LidarSearchAckRef::try_from_bytes(&buffer[..len])
     }
 }
 
 #[derive(Immutable, IntoBytes)]
-struct LidarSearchCmdPacket {
-    #[expect(unused)]
-    header: SdkPacketHeader,
+pub(crate) struct LidarSearchCmdPacket {
+    pub(crate) header: SdkPacketHeader,
 }
 
 impl LidarSearchCmdPacket {
     pub fn new(cmd_type: CommandType, sender_type: SendType) -> Self {
         let cmd_id = CommandID::QueryDeviceType;
 
-        let mut header = SdkPacketHeader::new(0, cmd_id, cmd_type, sender_type);
-        header.crc16_h = crate::crc::CRC16.checksum(header.as_bytes());
-        header.crc32_d = crate::crc::CRC32.checksum(header.as_bytes());
+        let header = SdkPacketHeader::new(0, cmd_id, cmd_type, sender_type).with_checksums(&[]);
 
         Self { header }
     }
@@ -128,16 +198,16 @@ pub struct LidarSearchAckRef<'a> {
 
 impl<'a> LidarSearchAckRef<'a> {
     pub fn try_from_bytes(source: &'a [u8]) -> Result<Self, crate::Error> {
-        let (header, data) = SdkPacketHeader::try_ref_from_prefix(source)?;
+        let (header, data) = SdkPacketHeader::try_ref_from_frame(source)?;
         let cmd_id = header.cmd_id;
 
         let CommandID::QueryDeviceType = cmd_id else {
-            return Err(crate::Error::unknown_type(
-                CommandID::QueryDeviceType,
-                cmd_id,
-            ));
+            return Err(crate::Error::WrongCommand {
+                expected: CommandID::QueryDeviceType,
+                found: cmd_id,
+            });
         };
-        let data = QueryDeviceTypeAck::try_ref_from_bytes(&data[..header.data_len()])?;
+        let data = QueryDeviceTypeAck::try_ref_from_bytes(data)?;
         Ok(Self { header, data })
     }
 }
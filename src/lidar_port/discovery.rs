@@ -0,0 +1,243 @@
+//! Broadcast discovery of all the lidars on the network segment.
+use std::net::Ipv4Addr;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use futures_core::Stream;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use zerocopy::IntoBytes;
+
+#[cfg(feature = "async-net")]
+use crate::runtime::AsyncNet;
+use crate::types::{sdk_packet::LivoxLidarDeviceType, state_info::trim_c_str};
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use crate::{
+    runtime::{self, Runtime, UdpSocket},
+    types::sdk_packet::{CommandType, SendType},
+};
+
+use super::detection::LidarSearchAckRef;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use super::{SocketPortConfig, detection::LidarSearchCmdPacket};
+
+/// A lidar found by [`Discovery`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    pub dev_type: LivoxLidarDeviceType,
+    pub sn: [u8; 16],
+    pub lidar_ip: Ipv4Addr,
+    pub cmd_port: u16,
+}
+
+impl DeviceInfo {
+    /// The serial number, trailing `\0` are trimmed.
+    pub fn sn_str(&self) -> Option<&str> {
+        trim_c_str(&self.sn)
+    }
+}
+
+impl From<&LidarSearchAckRef<'_>> for DeviceInfo {
+    fn from(ack: &LidarSearchAckRef<'_>) -> Self {
+        let data = ack.data;
+        Self {
+            dev_type: data.dev_type,
+            sn: data.sn,
+            lidar_ip: data.lidar_ip.into(),
+            cmd_port: data.cmd_port,
+        }
+    }
+}
+
+/// Event emitted by [`Discovery::watch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceEvent {
+    Appeared(DeviceInfo),
+    Disappeared(DeviceInfo),
+}
+
+/// Discovers the lidars by broadcasting [`QueryDeviceType`](crate::types::sdk_packet::CommandID::QueryDeviceType),
+/// unlike the [`DetectionPort`](super::DetectionPort), the lidar IPs don't need to be known.
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+pub struct Discovery<R: Runtime> {
+    socket: R::UdpSocket,
+    broadcast_socket: R::UdpSocket,
+    buffer: Vec<u8>,
+    broadcast_buffer: Vec<u8>,
+    lidar_port: u16,
+}
+
+#[cfg(feature = "async-net")]
+impl Discovery<AsyncNet> {
+    /// Creates the port on [`AsyncNet`], see also [`new_with_runtime`](Self::new_with_runtime).
+    pub async fn new(
+        local_ip: impl Into<Ipv4Addr>,
+        detection_port: &SocketPortConfig,
+        buffer_init_size: usize,
+    ) -> crate::Result<Self> {
+        Self::new_with_runtime(local_ip, detection_port, buffer_init_size).await
+    }
+
+    pub async fn new_default(local_ip: impl Into<Ipv4Addr>) -> crate::Result<Self> {
+        Self::new_default_with_runtime(local_ip).await
+    }
+}
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+impl<R: Runtime> Discovery<R> {
+    const DEFAULT_BUFFER_INIT_SIZE: usize = 48;
+
+    pub async fn new_with_runtime(
+        local_ip: impl Into<Ipv4Addr>,
+        detection_port: &SocketPortConfig,
+        buffer_init_size: usize,
+    ) -> crate::Result<Self> {
+        let local_ip = local_ip.into();
+
+        let broadcast_socket = R::UdpSocket::bind(SocketAddr::from((
+            Ipv4Addr::BROADCAST,
+            detection_port.local,
+        )))
+        .await?;
+
+        let socket = R::UdpSocket::bind(SocketAddr::from((local_ip, detection_port.local))).await?;
+        socket.set_broadcast(true)?;
+
+        Ok(Self {
+            socket,
+            broadcast_socket,
+            buffer: vec![0; buffer_init_size],
+            broadcast_buffer: vec![0; buffer_init_size],
+            lidar_port: detection_port.lidar,
+        })
+    }
+    pub async fn new_default_with_runtime(local_ip: impl Into<Ipv4Addr>) -> crate::Result<Self> {
+        Self::new_with_runtime(
+            local_ip,
+            &SocketPortConfig::new_detection_port_config(),
+            Self::DEFAULT_BUFFER_INIT_SIZE,
+        )
+        .await
+    }
+
+    /// Broadcasts a query and collects all the lidars responding within the `window`,
+    /// the lidars are deduplicated by their SN.
+    pub async fn discover(&mut self, window: Duration) -> crate::Result<Vec<DeviceInfo>> {
+        let packet = LidarSearchCmdPacket::new(CommandType::Cmd, SendType::HostSend);
+        self.socket
+            .send_to(
+                packet.as_bytes(),
+                SocketAddr::from((Ipv4Addr::BROADCAST, self.lidar_port)),
+            )
+            .await?;
+
+        let deadline = Instant::now() + window;
+        let mut devices = Vec::<DeviceInfo>::new();
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let Some(device) = runtime::timeout::<R, _>(remaining, self.recv_device()).await else {
+                break;
+            };
+            if let Some(device) = device?
+                && devices.iter().all(|known| known.sn != device.sn)
+            {
+                devices.push(device);
+            }
+        }
+        Ok(devices)
+    }
+
+    /// Returns a stream which runs [`discover`](Self::discover) every `interval`,
+    /// and emits the lidars as they appear,
+    /// or disappear when they have not responded for longer than `timeout`.
+    ///
+    /// The stream ends if the socket fails.
+    ///
+    /// Note that the returned stream does not implement the [`Unpin`],
+    /// so you need to [`pin`](std::pin::pin) it if you want to consume it.
+    pub fn watch(self, interval: Duration, timeout: Duration) -> impl Stream<Item = DeviceEvent> {
+        let state = WatchState {
+            discovery: self,
+            known: HashMap::new(),
+            events: VecDeque::new(),
+        };
+        futures_lite::stream::unfold(state, move |mut state| async move {
+            loop {
+                if let Some(event) = state.events.pop_front() {
+                    return Some((event, state));
+                }
+                let devices = state.discovery.discover(interval).await.ok()?;
+                state.update(devices, timeout);
+            }
+        })
+    }
+
+    /// Receives a response from either the unicast or the broadcast socket.
+    async fn recv_device(&mut self) -> crate::Result<Option<DeviceInfo>> {
+        let Self {
+            socket,
+            broadcast_socket,
+            buffer,
+            broadcast_buffer,
+            ..
+        } = self;
+        let packet = futures_lite::future::or(
+            async {
+                let (len, _) = socket.recv_from(buffer).await?;
+                io::Result::Ok(&buffer[..len])
+            },
+            async {
+                let (len, _) = broadcast_socket.recv_from(broadcast_buffer).await?;
+                io::Result::Ok(&broadcast_buffer[..len])
+            },
+        )
+        .await?;
+
+        let device = LidarSearchAckRef::try_from_bytes(packet)
+            .ok()
+            .filter(|ack| ack.header.cmd_type == CommandType::Ack && ack.data.is_valid())
+            .as_ref()
+            .map(DeviceInfo::from);
+        Ok(device)
+    }
+}
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+struct WatchState<R: Runtime> {
+    discovery: Discovery<R>,
+    known: HashMap<[u8; 16], (DeviceInfo, Instant)>,
+    events: VecDeque<DeviceEvent>,
+}
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+impl<R: Runtime> WatchState<R> {
+    fn update(&mut self, devices: Vec<DeviceInfo>, timeout: Duration) {
+        let now = Instant::now();
+        for device in devices {
+            match self.known.get_mut(&device.sn) {
+                Some((known, last_seen)) => {
+                    *known = device;
+                    *last_seen = now;
+                }
+                None => {
+                    self.events.push_back(DeviceEvent::Appeared(device.clone()));
+                    self.known.insert(device.sn, (device, now));
+                }
+            }
+        }
+
+        let events = &mut self.events;
+        self.known.retain(|_, (device, last_seen)| {
+            let alive = now.duration_since(*last_seen) <= timeout;
+            if !alive {
+                events.push_back(DeviceEvent::Disappeared(device.clone()));
+            }
+            alive
+        });
+    }
+}
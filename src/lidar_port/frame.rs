@@ -0,0 +1,372 @@
+//! Assembling of the point cloud packets into complete frames.
+use std::time::Duration;
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use futures_core::Stream;
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use crate::runtime::Runtime;
+use crate::types::point::{Point, PointTag, Tagged};
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use super::PointDataPort;
+use super::point_data::PointPacketRef;
+
+/// How the packets are split into frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameSplit {
+    /// A new frame starts when [`frame_cnt`](crate::types::ethernet::EthernetPacketHeader::frame_cnt) changes,
+    /// for the repetitive scan.
+    FrameCnt,
+    /// A new frame starts when the packet [`timestamp`](crate::types::ethernet::EthernetPacketHeader::timestamp)
+    /// is out of the window from the first packet of the frame,
+    /// for the non-repetitive scan where [`frame_cnt`](crate::types::ethernet::EthernetPacketHeader::frame_cnt) is invalid.
+    TimeWindow(Duration),
+}
+
+/// A point of the [`Frame`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FramePoint {
+    /// Unit: m
+    pub x: f32,
+    /// Unit: m
+    pub y: f32,
+    /// Unit: m
+    pub z: f32,
+    pub reflectivity: u8,
+    pub tag: PointTag,
+    /// Interpolated from the packet timestamp, Unit: ns
+    pub timestamp: u64,
+}
+
+impl Tagged for FramePoint {
+    fn point_tag(&self) -> PointTag {
+        self.tag
+    }
+}
+
+/// A run of packets missing before a received packet,
+/// found by the discontinuous [`udp_cnt`](crate::types::ethernet::EthernetPacketHeader::udp_cnt),
+/// which shrinks when the late packets arrive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UdpCntGap {
+    /// The `udp_cnt` of the first missing packet.
+    pub start: u16,
+    /// Number of the missing packets.
+    pub missing: u16,
+}
+
+/// An owned point cloud frame.
+#[derive(Debug, Clone, Default)]
+pub struct Frame {
+    /// The [`frame_cnt`](crate::types::ethernet::EthernetPacketHeader::frame_cnt) of the first packet.
+    pub frame_cnt: u8,
+    /// Timestamp of the first packet, Unit: ns
+    pub timestamp: u64,
+    /// Number of the packets received in this frame.
+    pub packet_count: usize,
+    pub points: Vec<FramePoint>,
+    pub gaps: Vec<UdpCntGap>,
+}
+
+impl Frame {
+    /// Total number of the packets missing in this frame.
+    pub fn missing_packets(&self) -> usize {
+        self.gaps.iter().map(|gap| gap.missing as usize).sum()
+    }
+
+    /// Timestamp of the last point, Unit: ns
+    pub fn end_timestamp(&self) -> u64 {
+        self.points
+            .last()
+            .map_or(self.timestamp, |point| point.timestamp)
+    }
+}
+
+/// Collects the packets into [`Frame`]s, see also [`PointDataPort::into_frame_stream`].
+#[derive(Debug)]
+pub struct FrameAssembler {
+    split: FrameSplit,
+    frame: Option<Frame>,
+    /// The expected `udp_cnt` of the next packet, unknown before the first packet.
+    next_udp_cnt: Option<u16>,
+}
+
+impl FrameAssembler {
+    pub fn new(split: FrameSplit) -> Self {
+        Self {
+            split,
+            frame: None,
+            next_udp_cnt: None,
+        }
+    }
+
+    /// Adds the packet to the current frame, and returns the previous frame if the packet starts a new one.
+    pub fn push(&mut self, packet: &PointPacketRef) -> Option<Frame> {
+        let header = packet.header;
+        let (frame_cnt, udp_cnt, timestamp) = (header.frame_cnt, header.udp_cnt, header.timestamp);
+
+        let is_new_frame = self.frame.as_ref().is_some_and(|frame| match self.split {
+            FrameSplit::FrameCnt => frame.frame_cnt != frame_cnt,
+            FrameSplit::TimeWindow(window) => {
+                timestamp.abs_diff(frame.timestamp) >= window.as_nanos() as u64
+            }
+        });
+        let finished = if is_new_frame {
+            self.frame.take()
+        } else {
+            None
+        };
+
+        let frame = self.frame.get_or_insert_with(|| {
+            // The udp_cnt is cleared at the beginning of each frame,
+            // but the first frame may be joined halfway, where the expected one is unknown.
+            if let (FrameSplit::FrameCnt, Some(_)) = (self.split, self.next_udp_cnt) {
+                self.next_udp_cnt = Some(0);
+            }
+            Frame {
+                frame_cnt,
+                timestamp,
+                ..Default::default()
+            }
+        });
+
+        match self.next_udp_cnt {
+            Some(next_udp_cnt) => {
+                let missing = udp_cnt.wrapping_sub(next_udp_cnt);
+                if missing < u16::MAX / 2 {
+                    if missing > 0 {
+                        frame.gaps.push(UdpCntGap {
+                            start: next_udp_cnt,
+                            missing,
+                        });
+                    }
+                    self.next_udp_cnt = Some(udp_cnt.wrapping_add(1));
+                } else {
+                    // The reordered or duplicated packets are behind the expected one,
+                    // which may fill a gap found before.
+                    fill_gap(&mut frame.gaps, udp_cnt);
+                }
+            }
+            None => self.next_udp_cnt = Some(udp_cnt.wrapping_add(1)),
+        }
+
+        frame.packet_count += 1;
+        push_points(&mut frame.points, packet);
+        finished
+    }
+
+    /// Takes the current incomplete frame, if any.
+    pub fn flush(&mut self) -> Option<Frame> {
+        self.frame.take()
+    }
+}
+
+/// Removes the late packet `udp_cnt` from the gap containing it, if any.
+fn fill_gap(gaps: &mut Vec<UdpCntGap>, udp_cnt: u16) {
+    let Some(index) = gaps
+        .iter()
+        .position(|gap| udp_cnt.wrapping_sub(gap.start) < gap.missing)
+    else {
+        return;
+    };
+    let gap = gaps[index];
+    let before = udp_cnt.wrapping_sub(gap.start);
+    let after = gap.missing - before - 1;
+    let split = [
+        UdpCntGap {
+            start: gap.start,
+            missing: before,
+        },
+        UdpCntGap {
+            start: udp_cnt.wrapping_add(1),
+            missing: after,
+        },
+    ];
+    gaps.splice(
+        index..=index,
+        split.into_iter().filter(|gap| gap.missing > 0),
+    );
+}
+
+fn push_points(points: &mut Vec<FramePoint>, packet: &PointPacketRef) {
+    points.extend(packet.iter_with_timestamps().map(|(point, timestamp)| {
+        let Point {
+            x,
+            y,
+            z,
+            reflectivity,
+            tag,
+        } = point.into();
+        FramePoint {
+            x,
+            y,
+            z,
+            reflectivity,
+            tag,
+            timestamp,
+        }
+    }));
+}
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+impl<R: Runtime> PointDataPort<R> {
+    /// Returns a stream of the frames assembled from the received packets.
+    ///
+    /// The packets failing to parse are skipped, and the stream ends if the socket fails.
+    ///
+    /// Note that the returned stream does not implement the [`Unpin`],
+    /// so you need to [`pin`](std::pin::pin) it if you want to consume it.
+    pub fn into_frame_stream(self, split: FrameSplit) -> impl Stream<Item = Frame> {
+        let assembler = FrameAssembler::new(split);
+        futures_lite::stream::unfold((self, assembler), |(mut port, mut assembler)| async {
+            loop {
+                let packet = match port.next_packet_ref().await {
+                    Ok(packet) => packet,
+                    Err(crate::Error::Io(_)) => return None,
+                    Err(_) => continue,
+                };
+                if let Some(frame) = assembler.push(&packet) {
+                    return Some((frame, (port, assembler)));
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A packet of one cartesian high point.
+    fn packet(frame_cnt: u8, udp_cnt: u16, timestamp: u64) -> Vec<u8> {
+        const LEN: u16 = 36 + 14;
+        let mut packet = vec![0; LEN as usize];
+        packet[1..3].copy_from_slice(&LEN.to_le_bytes());
+        packet[5..7].copy_from_slice(&1u16.to_le_bytes());
+        packet[7..9].copy_from_slice(&udp_cnt.to_le_bytes());
+        packet[9] = frame_cnt;
+        packet[10] = 1;
+        packet[28..36].copy_from_slice(&timestamp.to_le_bytes());
+        packet
+    }
+
+    fn push_all(assembler: &mut FrameAssembler, packets: &[(u8, u16, u64)]) -> Vec<Frame> {
+        packets
+            .iter()
+            .filter_map(|&(frame_cnt, udp_cnt, timestamp)| {
+                let packet = packet(frame_cnt, udp_cnt, timestamp);
+                assembler.push(&PointPacketRef::try_from_bytes(&packet).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_by_frame_cnt() {
+        let mut assembler = FrameAssembler::new(FrameSplit::FrameCnt);
+        let frames = push_all(
+            &mut assembler,
+            &[(0, 0, 0), (0, 1, 1), (1, 0, 2), (2, 0, 3)],
+        );
+        assert_eq!(frames.len(), 2);
+        assert_eq!((frames[0].frame_cnt, frames[0].packet_count), (0, 2));
+        assert_eq!((frames[1].frame_cnt, frames[1].packet_count), (1, 1));
+        assert_eq!(frames[0].points.len(), 2);
+        assert!(frames.iter().all(|frame| frame.gaps.is_empty()));
+        assert_eq!(assembler.flush().map(|frame| frame.frame_cnt), Some(2));
+    }
+
+    #[test]
+    fn first_frame_joined_halfway_has_no_gap() {
+        let mut assembler = FrameAssembler::new(FrameSplit::FrameCnt);
+        let frames = push_all(&mut assembler, &[(0, 40, 0), (0, 41, 1), (1, 0, 2)]);
+        assert_eq!(frames[0].gaps, []);
+    }
+
+    #[test]
+    fn gaps() {
+        let mut assembler = FrameAssembler::new(FrameSplit::FrameCnt);
+        let frames = push_all(
+            &mut assembler,
+            &[
+                (0, 5, 0),
+                (0, 6, 1),
+                (0, 9, 2),
+                (1, 2, 3),
+                (1, 3, 4),
+                (2, 0, 5),
+            ],
+        );
+        assert_eq!(
+            frames[0].gaps,
+            [UdpCntGap {
+                start: 7,
+                missing: 2
+            }]
+        );
+        assert_eq!(
+            frames[1].gaps,
+            [UdpCntGap {
+                start: 0,
+                missing: 2
+            }]
+        );
+        assert_eq!(frames[1].missing_packets(), 2);
+    }
+
+    #[test]
+    fn reordered_packet_is_not_a_gap() {
+        let mut assembler = FrameAssembler::new(FrameSplit::FrameCnt);
+        let frames = push_all(
+            &mut assembler,
+            &[(0, 0, 0), (0, 2, 1), (0, 1, 2), (0, 3, 3), (1, 0, 4)],
+        );
+        assert_eq!(frames[0].gaps, []);
+        assert_eq!(frames[0].packet_count, 4);
+    }
+
+    #[test]
+    fn late_packet_shrinks_the_gap() {
+        let mut assembler = FrameAssembler::new(FrameSplit::FrameCnt);
+        let frames = push_all(
+            &mut assembler,
+            &[
+                (0, 0, 0),
+                (0, 6, 1),
+                (0, 1, 2),
+                (0, 3, 3),
+                (0, 7, 4),
+                (0, 3, 5),
+                (1, 0, 6),
+            ],
+        );
+        assert_eq!(
+            frames[0].gaps,
+            [
+                UdpCntGap {
+                    start: 2,
+                    missing: 1
+                },
+                UdpCntGap {
+                    start: 4,
+                    missing: 2
+                }
+            ]
+        );
+        assert_eq!(frames[0].missing_packets(), 3);
+    }
+
+    #[test]
+    fn split_by_time_window() {
+        let mut assembler = FrameAssembler::new(FrameSplit::TimeWindow(Duration::from_nanos(100)));
+        let frames = push_all(
+            &mut assembler,
+            &[(0, 0, 0), (0, 1, 99), (0, 2, 100), (0, 3, 150)],
+        );
+        assert_eq!(frames.len(), 1);
+        assert_eq!((frames[0].timestamp, frames[0].packet_count), (0, 2));
+        assert_eq!(frames[0].gaps, []);
+        let last = assembler.flush().unwrap();
+        assert_eq!((last.timestamp, last.packet_count), (100, 2));
+    }
+}
@@ -1,39 +1,87 @@
-use std::io;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use std::net::SocketAddr;
 
-use async_net::{AsyncToSocketAddrs, UdpSocket};
+#[cfg(any(feature = "async-net", feature = "tokio"))]
 use futures_core::Stream;
 use zerocopy::TryFromBytes;
 
+#[cfg(feature = "async-net")]
+use crate::runtime::AsyncNet;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use crate::runtime::{self, Runtime, UdpSocket};
 use crate::types::ethernet::{EthernetPacketHeader, ImuData, PointDataType};
 
 use super::SocketPortConfig;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use super::{CrcCheck, PortStats, stats::StatsTracker};
 
-pub struct ImuPort {
-    socket: UdpSocket,
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+pub struct ImuPort<R: Runtime> {
+    socket: R::UdpSocket,
     buffer: Vec<u8>,
+    crc_check: CrcCheck,
+    stats: StatsTracker,
 }
 
-impl ImuPort {
+#[cfg(feature = "async-net")]
+impl ImuPort<AsyncNet> {
+    /// Creates the port on [`AsyncNet`], see also [`new_with_runtime`](Self::new_with_runtime).
+    pub async fn new(
+        local_addr: impl Into<SocketAddr>,
+        lidar_addr: impl Into<SocketAddr>,
+        buffer_init_size: usize,
+    ) -> crate::Result<Self> {
+        Self::new_with_runtime(local_addr, lidar_addr, buffer_init_size).await
+    }
+}
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+impl<R: Runtime> ImuPort<R> {
     const DEFAULT_BUFFER_INIT_SIZE: usize = 60;
 
-    pub async fn new(
-        local_addr: impl AsyncToSocketAddrs,
-        lidar_addr: impl AsyncToSocketAddrs,
+    pub async fn new_with_runtime(
+        local_addr: impl Into<SocketAddr>,
+        lidar_addr: impl Into<SocketAddr>,
         buffer_init_size: usize,
-    ) -> Result<Self, io::Error> {
-        let socket = UdpSocket::bind(local_addr).await?;
-        socket.connect(lidar_addr).await?;
+    ) -> crate::Result<Self> {
+        let socket = runtime::connect::<R>(local_addr.into(), lidar_addr.into()).await?;
 
         Ok(Self {
             socket,
             buffer: vec![0; buffer_init_size],
+            crc_check: CrcCheck::default(),
+            stats: StatsTracker::default(),
         })
     }
 
-    pub async fn next_packet_ref(&mut self) -> Result<ImuPacketRef<'_>, io::Error> {
+    /// Sets how the received packets are verified, see also [`CrcCheck`].
+    pub fn with_crc_check(mut self, crc_check: CrcCheck) -> Self {
+        self.crc_check = crc_check;
+        self
+    }
+
+    /// Number of the packets failing the CRC verification so far.
+    pub fn crc_failures(&self) -> u64 {
+        self.stats.snapshot().crc_failures
+    }
+
+    /// Returns a snapshot of the counters since the port was created.
+    pub fn stats(&self) -> PortStats {
+        self.stats.snapshot()
+    }
+
+    pub async fn next_packet_ref(&mut self) -> crate::Result<ImuPacketRef<'_>> {
         let buffer = self.buffer.as_mut();
-        let len = self.socket.recv(buffer).await?;
-        ImuPacketRef::try_from_bytes(&buffer[..len]).map_err(From::from)
+        let len = loop {
+            let len = self.socket.recv(buffer).await?;
+            if self.stats.accept(self.crc_check, &buffer[..len])? {
+                break len;
+            }
+        };
+        self.stats
+            .record_parsed(ImuPacketRef::try_from_bytes(&buffer[..len]), |packet| {
+                packet.header
+            })
     }
 
     /// Returns a stream and using the given closure to map each packet to an item.
@@ -63,32 +111,55 @@ impl SocketPortConfig {
     }
 }
 
+#[cfg(any(feature = "async-net", feature = "tokio"))]
 impl super::IpConfig {
+    #[cfg(feature = "async-net")]
     pub async fn new_imu_port(
         &self,
         imu_port: &SocketPortConfig,
         buffer_init_size: usize,
-    ) -> Result<ImuPort, io::Error> {
-        ImuPort::new(
+    ) -> crate::Result<ImuPort<AsyncNet>> {
+        self.new_imu_port_with_runtime(imu_port, buffer_init_size)
+            .await
+    }
+    #[cfg(feature = "async-net")]
+    pub async fn new_default_imu_port(&self) -> crate::Result<ImuPort<AsyncNet>> {
+        self.new_default_imu_port_with_runtime().await
+    }
+
+    /// Same as [`new_imu_port`](Self::new_imu_port), but on the given [`Runtime`].
+    pub async fn new_imu_port_with_runtime<R: Runtime>(
+        &self,
+        imu_port: &SocketPortConfig,
+        buffer_init_size: usize,
+    ) -> crate::Result<ImuPort<R>> {
+        ImuPort::new_with_runtime(
             (self.local, imu_port.local),
             (self.lidar, imu_port.lidar),
             buffer_init_size,
         )
         .await
     }
-    pub async fn new_default_imu_port(&self) -> Result<ImuPort, io::Error> {
-        self.new_imu_port(
+    pub async fn new_default_imu_port_with_runtime<R: Runtime>(&self) -> crate::Result<ImuPort<R>> {
+        self.new_imu_port_with_runtime(
             &SocketPortConfig::new_imu_port_config(),
-            ImuPort::DEFAULT_BUFFER_INIT_SIZE,
+            ImuPort::<R>::DEFAULT_BUFFER_INIT_SIZE,
         )
         .await
     }
 }
 
+#[cfg(any(feature = "async-net", feature = "tokio"))]
 impl super::LidarPortConfig {
-    pub async fn new_imu_port(&self) -> Result<ImuPort, io::Error> {
+    #[cfg(feature = "async-net")]
+    pub async fn new_imu_port(&self) -> crate::Result<ImuPort<AsyncNet>> {
+        self.new_imu_port_with_runtime().await
+    }
+
+    /// Same as [`new_imu_port`](Self::new_imu_port), but on the given [`Runtime`].
+    pub async fn new_imu_port_with_runtime<R: Runtime>(&self) -> crate::Result<ImuPort<R>> {
         self.ip
-            .new_imu_port(&self.port, self.buffer_init_size)
+            .new_imu_port_with_runtime(&self.port, self.buffer_init_size)
             .await
     }
 }
@@ -103,10 +174,7 @@ impl<'a> ImuPacketRef<'a> {
         let (header, data) = EthernetPacketHeader::try_ref_from_prefix(source)?;
 
         let PointDataType::ImuData = header.data_type else {
-            return Err(crate::Error::unknown_type(
-                PointDataType::ImuData,
-                header.data_type,
-            ));
+            return Err(crate::Error::WrongDataType(header.data_type));
         };
         let data = ImuData::try_ref_from_bytes(data)?;
         Ok(Self { header, data })
@@ -0,0 +1,101 @@
+//! Selection of the host IP from the local network interfaces.
+use std::net::Ipv4Addr;
+
+use if_addrs::IfAddr;
+
+use super::{IpConfig, LidarPortConfig, SocketPortConfig, discovery::DeviceInfo};
+
+/// A local IPv4 network interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalInterface {
+    pub name: String,
+    pub ip: Ipv4Addr,
+    pub netmask: Ipv4Addr,
+}
+
+impl LocalInterface {
+    /// Returns `true` if the `ip` is on the same subnet as this interface.
+    pub fn contains(&self, ip: Ipv4Addr) -> bool {
+        let netmask = self.netmask.to_bits();
+        self.ip.to_bits() & netmask == ip.to_bits() & netmask
+    }
+}
+
+/// Lists all the IPv4 addresses of the local network interfaces.
+pub fn local_ipv4_interfaces() -> crate::Result<Vec<LocalInterface>> {
+    let interfaces = if_addrs::get_if_addrs()?
+        .into_iter()
+        .filter_map(|interface| match interface.addr {
+            IfAddr::V4(addr) => Some(LocalInterface {
+                name: interface.name,
+                ip: addr.ip,
+                netmask: addr.netmask,
+            }),
+            IfAddr::V6(_) => None,
+        })
+        .collect();
+    Ok(interfaces)
+}
+
+/// Picks the local interface on the same subnet as the lidar,
+/// the one with the longest netmask wins if there are many.
+///
+/// # Error
+///
+/// Returns [`Error::UnreachableLidar`](crate::Error::UnreachableLidar) if no interface is on the same subnet.
+pub fn select_local_interface(lidar_ip: Ipv4Addr) -> crate::Result<LocalInterface> {
+    local_ipv4_interfaces()?
+        .into_iter()
+        .filter(|interface| !interface.ip.is_loopback() && interface.contains(lidar_ip))
+        .max_by_key(|interface| interface.netmask.to_bits())
+        .ok_or(crate::Error::UnreachableLidar(lidar_ip))
+}
+
+impl IpConfig {
+    /// Creates the config with the `local` IP selected from the local interfaces,
+    /// see also [`select_local_interface`].
+    pub fn from_lidar_ip(lidar: impl Into<Ipv4Addr>) -> crate::Result<Self> {
+        let lidar = lidar.into();
+        let local = select_local_interface(lidar)?.ip;
+        Ok(Self { local, lidar })
+    }
+}
+
+impl LidarPortConfig {
+    /// Same as [`new`](Self::new), but the `local` IP is selected from the local interfaces,
+    /// see also [`select_local_interface`].
+    pub fn from_lidar_ip(
+        lidar_ip: impl Into<Ipv4Addr>,
+        port: SocketPortConfig,
+        buffer_init_size: usize,
+    ) -> crate::Result<Self> {
+        Ok(Self {
+            ip: IpConfig::from_lidar_ip(lidar_ip)?,
+            port,
+            buffer_init_size,
+        })
+    }
+}
+
+impl DeviceInfo {
+    /// Creates the [`IpConfig`] of the discovered lidar, see also [`IpConfig::from_lidar_ip`].
+    pub fn ip_config(&self) -> crate::Result<IpConfig> {
+        IpConfig::from_lidar_ip(self.lidar_ip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_subnet() {
+        let interface = LocalInterface {
+            name: "eth0".to_owned(),
+            ip: Ipv4Addr::new(192, 168, 1, 50),
+            netmask: Ipv4Addr::new(255, 255, 255, 0),
+        };
+        assert!(interface.contains(Ipv4Addr::new(192, 168, 1, 12)));
+        assert!(!interface.contains(Ipv4Addr::new(192, 168, 2, 12)));
+    }
+}
@@ -1,46 +1,102 @@
-use std::io;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use std::net::SocketAddr;
 
-use async_net::{AsyncToSocketAddrs, UdpSocket};
+#[cfg(any(feature = "async-net", feature = "tokio"))]
 use futures_core::Stream;
-use zerocopy::TryFromBytes;
+use zerocopy::{Immutable, KnownLayout, TryFromBytes};
 
+#[cfg(feature = "async-net")]
+use crate::runtime::AsyncNet;
+#[cfg(feature = "fugit")]
+use crate::types::ethernet::NanosInstantU64;
 use crate::{
     lidar_port::SocketPortConfig,
-    types::ethernet::{
-        CartesianHighPoint, CartesianLowPoint, EthernetPacketHeader, PointDataType, SphericalPoint,
+    types::{
+        ethernet::{
+            CartesianHighPoint, CartesianLowPoint, EthernetPacketHeader, PointDataType,
+            SphericalPoint, interpolate_timestamp,
+        },
+        point::{Point, PointTag, Tagged},
+        soa::{self, SoaPointsMut},
     },
 };
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use crate::{
+    lidar_port::{CrcCheck, PortStats, stats::StatsTracker},
+    runtime::{self, Runtime, UdpSocket},
+};
 
-pub struct PointDataPort {
-    socket: UdpSocket,
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+pub struct PointDataPort<R: Runtime> {
+    pub(super) socket: R::UdpSocket,
     buffer: Vec<u8>,
+    pub(super) crc_check: CrcCheck,
+    pub(super) stats: StatsTracker,
+}
+
+#[cfg(feature = "async-net")]
+impl PointDataPort<AsyncNet> {
+    /// Creates the port on [`AsyncNet`], see also [`new_with_runtime`](Self::new_with_runtime).
+    pub async fn new(
+        local_addr: impl Into<SocketAddr>,
+        lidar_addr: impl Into<SocketAddr>,
+        buffer_init_size: usize,
+    ) -> crate::Result<Self> {
+        Self::new_with_runtime(local_addr, lidar_addr, buffer_init_size).await
+    }
 }
 
-impl PointDataPort {
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+impl<R: Runtime> PointDataPort<R> {
     ///  1024 * 1024 * 200 in orginal livox sdk
     const DEFAULT_BUFFER_INIT_SIZE: usize = 1380;
 
-    pub async fn new(
-        local_addr: impl AsyncToSocketAddrs,
-        lidar_addr: impl AsyncToSocketAddrs,
+    pub async fn new_with_runtime(
+        local_addr: impl Into<SocketAddr>,
+        lidar_addr: impl Into<SocketAddr>,
         buffer_init_size: usize,
-    ) -> Result<Self, io::Error> {
-        let socket = UdpSocket::bind(local_addr).await?;
-        socket.connect(lidar_addr).await?;
+    ) -> crate::Result<Self> {
+        let socket = runtime::connect::<R>(local_addr.into(), lidar_addr.into()).await?;
 
         Ok(Self {
             socket,
             buffer: vec![0; buffer_init_size],
+            crc_check: CrcCheck::default(),
+            stats: StatsTracker::default(),
         })
     }
 
+    /// Sets how the received packets are verified, see also [`CrcCheck`].
+    pub fn with_crc_check(mut self, crc_check: CrcCheck) -> Self {
+        self.crc_check = crc_check;
+        self
+    }
+
+    /// Number of the packets failing the CRC verification so far.
+    pub fn crc_failures(&self) -> u64 {
+        self.stats.snapshot().crc_failures
+    }
+
+    /// Returns a snapshot of the counters since the port was created.
+    pub fn stats(&self) -> PortStats {
+        self.stats.snapshot()
+    }
+
     /// # Error
     ///
     /// Fail if the socket is not connected.
-    pub async fn next_packet_ref(&mut self) -> Result<PointPacketRef<'_>, io::Error> {
+    pub async fn next_packet_ref(&mut self) -> crate::Result<PointPacketRef<'_>> {
         let buffer = self.buffer.as_mut();
-        let len = self.socket.recv(buffer).await?;
-        PointPacketRef::try_from_bytes(&buffer[..len]).map_err(From::from)
+        let len = loop {
+            let len = self.socket.recv(buffer).await?;
+            if self.stats.accept(self.crc_check, &buffer[..len])? {
+                break len;
+            }
+        };
+        self.stats
+            .record_parsed(PointPacketRef::try_from_bytes(&buffer[..len]), |packet| {
+                packet.header
+            })
     }
 
     /// Returns a stream and using the given closure to map each packet to an item.
@@ -70,43 +126,70 @@ impl SocketPortConfig {
     }
 }
 
+#[cfg(any(feature = "async-net", feature = "tokio"))]
 impl super::IpConfig {
+    #[cfg(feature = "async-net")]
     pub async fn new_point_data_port(
         &self,
         point_data_port: &SocketPortConfig,
         buffer_init_size: usize,
-    ) -> Result<PointDataPort, io::Error> {
-        PointDataPort::new(
+    ) -> crate::Result<PointDataPort<AsyncNet>> {
+        self.new_point_data_port_with_runtime(point_data_port, buffer_init_size)
+            .await
+    }
+    #[cfg(feature = "async-net")]
+    pub async fn new_default_point_data_port(&self) -> crate::Result<PointDataPort<AsyncNet>> {
+        self.new_default_point_data_port_with_runtime().await
+    }
+
+    /// Same as [`new_point_data_port`](Self::new_point_data_port), but on the given [`Runtime`].
+    pub async fn new_point_data_port_with_runtime<R: Runtime>(
+        &self,
+        point_data_port: &SocketPortConfig,
+        buffer_init_size: usize,
+    ) -> crate::Result<PointDataPort<R>> {
+        PointDataPort::new_with_runtime(
             (self.local, point_data_port.local),
             (self.lidar, point_data_port.lidar),
             buffer_init_size,
         )
         .await
     }
-    pub async fn new_default_point_data_port(&self) -> Result<PointDataPort, io::Error> {
-        self.new_point_data_port(
+    pub async fn new_default_point_data_port_with_runtime<R: Runtime>(
+        &self,
+    ) -> crate::Result<PointDataPort<R>> {
+        self.new_point_data_port_with_runtime(
             &SocketPortConfig::new_point_data_config(),
-            PointDataPort::DEFAULT_BUFFER_INIT_SIZE,
+            PointDataPort::<R>::DEFAULT_BUFFER_INIT_SIZE,
         )
         .await
     }
 }
 
+#[cfg(any(feature = "async-net", feature = "tokio"))]
 impl super::LidarPortConfig {
-    pub async fn new_point_data_port(&self) -> Result<PointDataPort, io::Error> {
+    #[cfg(feature = "async-net")]
+    pub async fn new_point_data_port(&self) -> crate::Result<PointDataPort<AsyncNet>> {
+        self.new_point_data_port_with_runtime().await
+    }
+
+    /// Same as [`new_point_data_port`](Self::new_point_data_port), but on the given [`Runtime`].
+    pub async fn new_point_data_port_with_runtime<R: Runtime>(
+        &self,
+    ) -> crate::Result<PointDataPort<R>> {
         self.ip
-            .new_point_data_port(&self.port, self.buffer_init_size)
+            .new_point_data_port_with_runtime(&self.port, self.buffer_init_size)
             .await
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct PointPacketRef<'a> {
     pub header: &'a EthernetPacketHeader,
     pub data: CoordinateDataRef<'a>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum CoordinateDataRef<'a> {
     /// the default data type
     CartesianHigh(&'a [CartesianHighPoint]),
@@ -121,6 +204,40 @@ impl<'a> PointPacketRef<'a> {
         let data = CoordinateDataRef::try_from_bytes_with_elems(data, header.data_type, dot_num)?;
         Ok(Self { header, data })
     }
+
+    /// Iterates over the points with their timestamps interpolated over the
+    /// [`time_interval`](EthernetPacketHeader::time_interval), see also [`EthernetPacketHeader::point_timestamp`].
+    pub fn iter_with_timestamps(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (PointRef<'a>, u64)> + use<'a> {
+        self.data
+            .iter_with_timestamps(self.header.timestamp, self.header.time_interval_ns())
+    }
+
+    /// See also [`CoordinateDataRef::points`].
+    pub fn points(&self) -> impl ExactSizeIterator<Item = Point> + use<'a> {
+        self.data.points()
+    }
+
+    /// Same as [`iter_with_timestamps`](Self::iter_with_timestamps), but yields the instants.
+    #[cfg(feature = "fugit")]
+    pub fn iter_with_instants(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (PointRef<'a>, NanosInstantU64)> + use<'a> {
+        self.iter_with_timestamps()
+            .map(|(point, timestamp)| (point, NanosInstantU64::from_ticks(timestamp)))
+    }
+}
+
+/// The points have no enum fields, so only the size can be invalid.
+fn points_from_bytes<P>(source: &[u8], count: usize) -> Result<&[P], crate::Error>
+where
+    [P]: TryFromBytes + KnownLayout<PointerMetadata = usize> + Immutable,
+{
+    <[P]>::try_ref_from_bytes_with_elems(source, count).map_err(|_| crate::Error::Truncated {
+        expected: count * size_of::<P>(),
+        found: source.len(),
+    })
 }
 
 impl<'a> CoordinateDataRef<'a> {
@@ -131,29 +248,138 @@ impl<'a> CoordinateDataRef<'a> {
     ) -> Result<Self, crate::Error> {
         let data = match data_type {
             PointDataType::CartesianCoordinateHighData => {
-                <[CartesianHighPoint]>::try_ref_from_bytes_with_elems(source, count)
-                    .map(CoordinateDataRef::CartesianHigh)?
+                CoordinateDataRef::CartesianHigh(points_from_bytes(source, count)?)
             }
             PointDataType::CartesianCoordinateLowData => {
-                <[CartesianLowPoint]>::try_ref_from_bytes_with_elems(source, count)
-                    .map(CoordinateDataRef::CartesianLow)?
+                CoordinateDataRef::CartesianLow(points_from_bytes(source, count)?)
             }
             PointDataType::SphericalCoordinateData => {
-                <[SphericalPoint]>::try_ref_from_bytes_with_elems(source, count)
-                    .map(CoordinateDataRef::Spherical)?
+                CoordinateDataRef::Spherical(points_from_bytes(source, count)?)
             }
             PointDataType::ImuData => {
-                return Err(crate::Error::unknown_type(
-                    format!(
-                        "{} | {} | {}",
-                        PointDataType::CartesianCoordinateHighData,
-                        PointDataType::CartesianCoordinateLowData,
-                        PointDataType::SphericalCoordinateData
-                    ),
-                    PointDataType::ImuData,
-                ));
+                return Err(crate::Error::WrongDataType(PointDataType::ImuData));
             }
         };
         Ok(data)
     }
+
+    pub fn len(&self) -> usize {
+        match self {
+            CoordinateDataRef::CartesianHigh(data) => data.len(),
+            CoordinateDataRef::CartesianLow(data) => data.len(),
+            CoordinateDataRef::Spherical(data) => data.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<PointRef<'a>> {
+        match *self {
+            CoordinateDataRef::CartesianHigh(data) => data.get(index).map(PointRef::CartesianHigh),
+            CoordinateDataRef::CartesianLow(data) => data.get(index).map(PointRef::CartesianLow),
+            CoordinateDataRef::Spherical(data) => data.get(index).map(PointRef::Spherical),
+        }
+    }
+
+    pub fn iter(&self) -> PointIter<'a> {
+        PointIter(*self)
+    }
+
+    /// Iterates over the points with their timestamps interpolated from the packet `timestamp`
+    /// over `time_interval_ns`, Unit: ns
+    ///
+    /// See also [`PointPacketRef::iter_with_timestamps`].
+    pub fn iter_with_timestamps(
+        &self,
+        timestamp: u64,
+        time_interval_ns: u64,
+    ) -> impl ExactSizeIterator<Item = (PointRef<'a>, u64)> + use<'a> {
+        let dot_num = self.len();
+        self.iter().enumerate().map(move |(index, point)| {
+            let timestamp = interpolate_timestamp(timestamp, time_interval_ns, dot_num, index);
+            (point, timestamp)
+        })
+    }
+
+    /// Iterates over the points normalized into [`Point`]s, without any allocation.
+    pub fn points(&self) -> impl ExactSizeIterator<Item = Point> + use<'a> {
+        self.iter().map(Point::from)
+    }
+
+    /// Writes the normalized points into the `buffer`, and returns the number of the written points,
+    /// which is less than [`len`](Self::len) if the `buffer` is too short.
+    pub fn write_points(&self, buffer: &mut [Point]) -> usize {
+        buffer
+            .iter_mut()
+            .zip(self.points())
+            .map(|(slot, point)| *slot = point)
+            .count()
+    }
+
+    /// Converts the points into the structure-of-arrays buffers, and returns the number of the converted points,
+    /// see also [`soa`].
+    pub fn write_soa(&self, out: SoaPointsMut) -> usize {
+        match self {
+            CoordinateDataRef::CartesianHigh(data) => soa::convert_cartesian_high(data, out),
+            CoordinateDataRef::CartesianLow(data) => soa::convert_cartesian_low(data, out),
+            CoordinateDataRef::Spherical(data) => soa::convert_spherical(data, out),
+        }
+    }
+}
+
+/// A point of any [`CoordinateDataRef`].
+#[derive(Debug, Clone, Copy)]
+pub enum PointRef<'a> {
+    CartesianHigh(&'a CartesianHighPoint),
+    CartesianLow(&'a CartesianLowPoint),
+    Spherical(&'a SphericalPoint),
+}
+
+impl Tagged for PointRef<'_> {
+    fn point_tag(&self) -> PointTag {
+        match self {
+            PointRef::CartesianHigh(point) => point.point_tag(),
+            PointRef::CartesianLow(point) => point.point_tag(),
+            PointRef::Spherical(point) => point.point_tag(),
+        }
+    }
+}
+
+impl From<PointRef<'_>> for Point {
+    fn from(point: PointRef<'_>) -> Self {
+        match point {
+            PointRef::CartesianHigh(point) => point.into(),
+            PointRef::CartesianLow(point) => point.into(),
+            PointRef::Spherical(point) => point.into(),
+        }
+    }
+}
+
+/// Iterator over the points of a [`CoordinateDataRef`].
+#[derive(Debug, Clone)]
+pub struct PointIter<'a>(CoordinateDataRef<'a>);
+
+impl<'a> Iterator for PointIter<'a> {
+    type Item = PointRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            CoordinateDataRef::CartesianHigh(data) => {
+                data.split_off_first().map(PointRef::CartesianHigh)
+            }
+            CoordinateDataRef::CartesianLow(data) => {
+                data.split_off_first().map(PointRef::CartesianLow)
+            }
+            CoordinateDataRef::Spherical(data) => data.split_off_first().map(PointRef::Spherical),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.len();
+        (len, Some(len))
+    }
 }
+
+impl ExactSizeIterator for PointIter<'_> {}
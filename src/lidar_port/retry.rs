@@ -0,0 +1,162 @@
+use std::time::{Duration, Instant};
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use crate::runtime::{self, Runtime};
+
+/// Timeout and retry policy of the sdk command exchanges.
+///
+/// Each attempt waits for at most [`timeout`](Self::timeout),
+/// and the delay between attempts starts from [`backoff`](Self::backoff),
+/// multiplied by [`backoff_factor`](Self::backoff_factor) after each retry, up to [`max_backoff`](Self::max_backoff).
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Timeout of each attempt.
+    pub timeout: Duration,
+    /// Number of retries after the first attempt.
+    pub retries: u32,
+    pub backoff: Duration,
+    pub backoff_factor: u32,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(1),
+            retries: 3,
+            backoff: Duration::from_millis(100),
+            backoff_factor: 2,
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Only one attempt, without any retry.
+    pub const fn no_retry(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            retries: 0,
+            backoff: Duration::ZERO,
+            backoff_factor: 1,
+            max_backoff: Duration::ZERO,
+        }
+    }
+
+    /// Runs the attempts until one of them completes in time.
+    ///
+    /// # Error
+    ///
+    /// Returns [`Error::Timeout`](crate::Error::Timeout) if all the attempts time out.
+    #[cfg(any(feature = "async-net", feature = "tokio"))]
+    pub(crate) async fn run<R: Runtime, T>(
+        &self,
+        mut attempt: impl AsyncFnMut() -> crate::Result<T>,
+    ) -> crate::Result<T> {
+        let mut backoffs = self.backoffs();
+        loop {
+            if let Some(result) = runtime::timeout::<R, _>(self.timeout, attempt()).await {
+                return result;
+            }
+            let Some(backoff) = backoffs.next() else {
+                break;
+            };
+            R::sleep(backoff).await;
+        }
+        Err(crate::Error::Timeout {
+            attempts: self.retries + 1,
+        })
+    }
+
+    /// Same as [`run`](Self::run), but blocks the current thread.
+    ///
+    /// Each attempt is given its deadline, and returns `None` if the deadline is passed.
+    pub(crate) fn run_blocking<T>(
+        &self,
+        mut attempt: impl FnMut(Instant) -> Option<crate::Result<T>>,
+    ) -> crate::Result<T> {
+        let mut backoffs = self.backoffs();
+        loop {
+            if let Some(result) = attempt(Instant::now() + self.timeout) {
+                return result;
+            }
+            let Some(backoff) = backoffs.next() else {
+                break;
+            };
+            std::thread::sleep(backoff);
+        }
+        Err(crate::Error::Timeout {
+            attempts: self.retries + 1,
+        })
+    }
+
+    /// The delays before each retry.
+    fn backoffs(&self) -> impl Iterator<Item = Duration> + use<> {
+        let (factor, max) = (self.backoff_factor, self.max_backoff);
+        std::iter::successors(Some(self.backoff), move |backoff| {
+            Some(
+                backoff
+                    .checked_mul(factor)
+                    .map_or(max, |next| next.min(max)),
+            )
+        })
+        .take(self.retries as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exponential_backoff() {
+        let policy = RetryPolicy {
+            retries: 5,
+            ..Default::default()
+        };
+        assert_eq!(
+            policy
+                .backoffs()
+                .map(|backoff| backoff.as_millis())
+                .collect::<Vec<_>>(),
+            [100, 200, 400, 800, 1000]
+        );
+        assert_eq!(RetryPolicy::default().backoffs().count(), 3);
+        assert_eq!(RetryPolicy::no_retry(Duration::ZERO).backoffs().count(), 0);
+    }
+
+    #[test]
+    fn backoff_saturates() {
+        let policy = RetryPolicy {
+            retries: 3,
+            backoff: Duration::from_secs(u64::MAX / 2),
+            backoff_factor: u32::MAX,
+            max_backoff: Duration::MAX,
+            ..Default::default()
+        };
+        assert!(
+            policy
+                .backoffs()
+                .skip(1)
+                .all(|backoff| backoff == Duration::MAX)
+        );
+    }
+
+    #[test]
+    fn blocking_attempts() {
+        let policy = RetryPolicy {
+            retries: 2,
+            backoff: Duration::ZERO,
+            ..Default::default()
+        };
+        let mut attempts = 0;
+        let result = policy.run_blocking(|_| {
+            attempts += 1;
+            (attempts == 3).then_some(Ok(attempts))
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        let result = policy.run_blocking(|_| None::<crate::Result<()>>);
+        assert!(matches!(result, Err(crate::Error::Timeout { attempts: 3 })));
+    }
+}
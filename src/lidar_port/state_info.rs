@@ -0,0 +1,183 @@
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use std::net::SocketAddr;
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use futures_core::Stream;
+use zerocopy::TryFromBytes;
+
+#[cfg(feature = "async-net")]
+use crate::runtime::AsyncNet;
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+use crate::runtime::{self, Runtime, UdpSocket};
+use crate::types::{
+    key_value::{KeyValueIter, KeyValueListHeader},
+    sdk_packet::{CommandID, SdkPacketHeader},
+    state_info::LidarStateInfo,
+};
+
+use super::SocketPortConfig;
+
+/// The port receiving the [`PushLidarInfo`](CommandID::PushLidarInfo) messages,
+/// which are pushed by the lidar periodically.
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+pub struct StateInfoPort<R: Runtime> {
+    socket: R::UdpSocket,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "async-net")]
+impl StateInfoPort<AsyncNet> {
+    /// Creates the port on [`AsyncNet`], see also [`new_with_runtime`](Self::new_with_runtime).
+    pub async fn new(
+        local_addr: impl Into<SocketAddr>,
+        lidar_addr: impl Into<SocketAddr>,
+        buffer_init_size: usize,
+    ) -> crate::Result<Self> {
+        Self::new_with_runtime(local_addr, lidar_addr, buffer_init_size).await
+    }
+}
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+impl<R: Runtime> StateInfoPort<R> {
+    /// Max length of an sdk frame.
+    const DEFAULT_BUFFER_INIT_SIZE: usize = 1400;
+
+    pub async fn new_with_runtime(
+        local_addr: impl Into<SocketAddr>,
+        lidar_addr: impl Into<SocketAddr>,
+        buffer_init_size: usize,
+    ) -> crate::Result<Self> {
+        let socket = runtime::connect::<R>(local_addr.into(), lidar_addr.into()).await?;
+
+        Ok(Self {
+            socket,
+            buffer: vec![0; buffer_init_size],
+        })
+    }
+
+    pub async fn next_packet_ref(&mut self) -> crate::Result<StateInfoPacketRef<'_>> {
+        let buffer = self.buffer.as_mut();
+        let len = self.socket.recv(buffer).await?;
+        StateInfoPacketRef::try_from_bytes(&buffer[..len])
+    }
+
+    /// Returns a stream and using the given closure to map each packet to an item.
+    ///
+    /// Note that the returned stream does not implement the [`Unpin`],
+    /// so you need to [`pin`](std::pin::pin) it if you want to consume it.
+    pub fn into_stream<Item>(
+        self,
+        f: impl FnMut(StateInfoPacketRef) -> Item,
+    ) -> impl Stream<Item = Item> {
+        futures_lite::stream::unfold((self, f), |(mut port, mut f)| async {
+            port.next_packet_ref()
+                .await
+                .map(&mut f)
+                .map(|item| (item, (port, f)))
+                .ok()
+        })
+    }
+}
+
+impl SocketPortConfig {
+    pub const fn new_state_info_config() -> Self {
+        Self {
+            local: 56201,
+            lidar: 56200,
+        }
+    }
+}
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+impl super::IpConfig {
+    #[cfg(feature = "async-net")]
+    pub async fn new_state_info_port(
+        &self,
+        state_info_port: &SocketPortConfig,
+        buffer_init_size: usize,
+    ) -> crate::Result<StateInfoPort<AsyncNet>> {
+        self.new_state_info_port_with_runtime(state_info_port, buffer_init_size)
+            .await
+    }
+    #[cfg(feature = "async-net")]
+    pub async fn new_default_state_info_port(&self) -> crate::Result<StateInfoPort<AsyncNet>> {
+        self.new_default_state_info_port_with_runtime().await
+    }
+
+    /// Same as [`new_state_info_port`](Self::new_state_info_port), but on the given [`Runtime`].
+    pub async fn new_state_info_port_with_runtime<R: Runtime>(
+        &self,
+        state_info_port: &SocketPortConfig,
+        buffer_init_size: usize,
+    ) -> crate::Result<StateInfoPort<R>> {
+        StateInfoPort::new_with_runtime(
+            (self.local, state_info_port.local),
+            (self.lidar, state_info_port.lidar),
+            buffer_init_size,
+        )
+        .await
+    }
+    pub async fn new_default_state_info_port_with_runtime<R: Runtime>(
+        &self,
+    ) -> crate::Result<StateInfoPort<R>> {
+        self.new_state_info_port_with_runtime(
+            &SocketPortConfig::new_state_info_config(),
+            StateInfoPort::<R>::DEFAULT_BUFFER_INIT_SIZE,
+        )
+        .await
+    }
+}
+
+#[cfg(any(feature = "async-net", feature = "tokio"))]
+impl super::LidarPortConfig {
+    #[cfg(feature = "async-net")]
+    pub async fn new_state_info_port(&self) -> crate::Result<StateInfoPort<AsyncNet>> {
+        self.new_state_info_port_with_runtime().await
+    }
+
+    /// Same as [`new_state_info_port`](Self::new_state_info_port), but on the given [`Runtime`].
+    pub async fn new_state_info_port_with_runtime<R: Runtime>(
+        &self,
+    ) -> crate::Result<StateInfoPort<R>> {
+        self.ip
+            .new_state_info_port_with_runtime(&self.port, self.buffer_init_size)
+            .await
+    }
+}
+
+#[derive(Debug)]
+pub struct StateInfoPacketRef<'a> {
+    pub header: &'a SdkPacketHeader,
+    pub list_header: &'a KeyValueListHeader,
+    /// The key-value items following the [`list_header`](Self::list_header).
+    pub data: &'a [u8],
+}
+
+impl<'a> StateInfoPacketRef<'a> {
+    pub fn try_from_bytes(source: &'a [u8]) -> Result<Self, crate::Error> {
+        let (header, data) = SdkPacketHeader::try_ref_from_frame(source)?;
+        let cmd_id = header.cmd_id;
+
+        let CommandID::PushLidarInfo = cmd_id else {
+            return Err(crate::Error::WrongCommand {
+                expected: CommandID::PushLidarInfo,
+                found: cmd_id,
+            });
+        };
+        let (list_header, data) = KeyValueListHeader::try_ref_from_prefix(data)?;
+        Ok(Self {
+            header,
+            list_header,
+            data,
+        })
+    }
+
+    pub fn key_values(&self) -> KeyValueIter<'a> {
+        KeyValueIter::new(self.list_header.key_num, self.data)
+    }
+
+    /// Decodes the pushed key-values into a [`LidarStateInfo`].
+    pub fn to_state_info(&self) -> Result<LidarStateInfo, crate::Error> {
+        LidarStateInfo::from_key_values(self.key_values())
+    }
+}
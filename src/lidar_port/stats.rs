@@ -0,0 +1,266 @@
+use std::time::{Duration, Instant};
+
+use crate::types::ethernet::EthernetPacketHeader;
+
+use super::CrcCheck;
+
+/// A snapshot of the counters of a data port, see [`PointDataPort::stats`](super::PointDataPort::stats).
+///
+/// The losses are found by the gaps in [`udp_cnt`](EthernetPacketHeader::udp_cnt),
+/// a late packet within the last [`PortStats::REORDER_WINDOW`] ones is counted as reordered instead of lost.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PortStats {
+    /// Number of the received datagrams, including the dropped and the failed ones.
+    pub received: u64,
+    pub lost: u64,
+    pub reordered: u64,
+    pub duplicated: u64,
+    pub crc_failures: u64,
+    pub parse_errors: u64,
+    /// Total size of the received datagrams.
+    pub bytes: u64,
+    /// Rolling estimate of the parsed packets per second.
+    pub packet_rate: f64,
+    /// Rolling estimate of the points per second, counted by the
+    /// [`dot_num`](EthernetPacketHeader::dot_num) of the parsed packets.
+    pub point_rate: f64,
+}
+
+impl PortStats {
+    /// Number of the latest packets remembered to tell the reordered ones from the duplicated ones.
+    pub const REORDER_WINDOW: u16 = 64;
+
+    /// The period over which the rates are estimated.
+    pub const RATE_WINDOW: Duration = Duration::from_secs(1);
+}
+
+/// Tracks the [`PortStats`] of the packets received by a port.
+#[derive(Debug, Default)]
+pub(crate) struct StatsTracker {
+    stats: PortStats,
+    /// The `frame_cnt` and the expected `udp_cnt` of the next packet, unknown before the first packet.
+    next: Option<(u8, u16)>,
+    /// Bit `i` is set if the packet `i + 1` behind the expected one has been received.
+    seen: u64,
+    /// The `next` and the `seen` of the previous frame, kept for its late packets
+    /// if the `udp_cnt` has been cleared at the beginning of the current frame.
+    prev: Option<((u8, u16), u64)>,
+    window_start: Option<Instant>,
+    window_packets: u64,
+    window_points: u64,
+}
+
+impl StatsTracker {
+    /// Records a received datagram and verifies it by the `crc_check`,
+    /// returns `false` if the datagram should be dropped.
+    pub(crate) fn accept(&mut self, crc_check: CrcCheck, datagram: &[u8]) -> crate::Result<bool> {
+        self.stats.received += 1;
+        self.stats.bytes += datagram.len() as u64;
+        crc_check.verify(
+            datagram,
+            &mut self.stats.crc_failures,
+            &mut self.stats.parse_errors,
+        )
+    }
+
+    /// Records the parsing result of an accepted datagram, `header` gets the header of the packet.
+    pub(crate) fn record_parsed<P>(
+        &mut self,
+        parsed: crate::Result<P>,
+        header: impl FnOnce(&P) -> &EthernetPacketHeader,
+    ) -> crate::Result<P> {
+        match &parsed {
+            Ok(packet) => self.record_packet(header(packet)),
+            Err(_) => self.stats.parse_errors += 1,
+        }
+        parsed
+    }
+
+    fn record_packet(&mut self, header: &EthernetPacketHeader) {
+        let (frame_cnt, udp_cnt, dot_num) = (header.frame_cnt, header.udp_cnt, header.dot_num);
+        self.record_udp_cnt(frame_cnt, udp_cnt);
+
+        let now = Instant::now();
+        let window_start = *self.window_start.get_or_insert(now);
+        self.window_packets += 1;
+        self.window_points += dot_num as u64;
+        let elapsed = now - window_start;
+        if elapsed >= PortStats::RATE_WINDOW {
+            (self.stats.packet_rate, self.stats.point_rate) = self.window_rates(elapsed);
+            self.window_start = Some(now);
+            (self.window_packets, self.window_points) = (0, 0);
+        }
+    }
+
+    fn record_udp_cnt(&mut self, frame_cnt: u8, udp_cnt: u16) {
+        let Some((next_frame_cnt, next_udp_cnt)) = self.next else {
+            self.next = Some((frame_cnt, udp_cnt.wrapping_add(1)));
+            self.seen = 1;
+            return;
+        };
+
+        let ahead = udp_cnt.wrapping_sub(next_udp_cnt);
+        if frame_cnt == next_frame_cnt.wrapping_sub(1) {
+            // A late packet of the previous frame, found in its own window if the udp_cnt has been cleared since.
+            match &mut self.prev {
+                Some(((prev_frame_cnt, prev_next_udp_cnt), prev_seen))
+                    if *prev_frame_cnt == frame_cnt =>
+                {
+                    let behind = prev_next_udp_cnt.wrapping_sub(udp_cnt);
+                    Self::record_late(&mut self.stats, prev_seen, behind);
+                }
+                _ => {
+                    let behind = next_udp_cnt.wrapping_sub(udp_cnt);
+                    Self::record_late(&mut self.stats, &mut self.seen, behind);
+                }
+            }
+            return;
+        }
+        let ahead = if frame_cnt != next_frame_cnt {
+            // The udp_cnt may be cleared at the beginning of each frame, count the gap by the closer one.
+            if udp_cnt < ahead {
+                self.prev = Some(((next_frame_cnt, next_udp_cnt), self.seen));
+                self.seen = 0;
+                udp_cnt
+            } else {
+                self.prev = None;
+                ahead
+            }
+        } else if ahead >= u16::MAX / 2 {
+            Self::record_late(
+                &mut self.stats,
+                &mut self.seen,
+                next_udp_cnt.wrapping_sub(udp_cnt),
+            );
+            return;
+        } else {
+            ahead
+        };
+
+        self.stats.lost += ahead as u64;
+        self.seen = self.seen.checked_shl(ahead as u32 + 1).unwrap_or_default() | 1;
+        self.next = Some((frame_cnt, udp_cnt.wrapping_add(1)));
+    }
+
+    /// Records the packet `behind` the expected one of the window, whose received packets are `seen`.
+    fn record_late(stats: &mut PortStats, seen: &mut u64, behind: u16) {
+        if !(1..=PortStats::REORDER_WINDOW).contains(&behind) {
+            // Out of the window, whether it has been counted as lost is unknown.
+            stats.reordered += 1;
+            return;
+        }
+        let bit = 1 << (behind - 1);
+        if *seen & bit != 0 {
+            stats.duplicated += 1;
+        } else {
+            *seen |= bit;
+            stats.reordered += 1;
+            stats.lost = stats.lost.saturating_sub(1);
+        }
+    }
+
+    fn window_rates(&self, elapsed: Duration) -> (f64, f64) {
+        let secs = elapsed.as_secs_f64();
+        (
+            self.window_packets as f64 / secs,
+            self.window_points as f64 / secs,
+        )
+    }
+
+    pub(crate) fn snapshot(&self) -> PortStats {
+        let mut stats = self.stats;
+        // Decays the rates if the packets stop arriving.
+        if let Some(window_start) = self.window_start {
+            let elapsed = window_start.elapsed();
+            if elapsed >= PortStats::RATE_WINDOW {
+                (stats.packet_rate, stats.point_rate) = self.window_rates(elapsed);
+            }
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the `(frame_cnt, udp_cnt)` of the packets in order.
+    fn track(packets: &[(u8, u16)]) -> PortStats {
+        let mut tracker = StatsTracker::default();
+        for &(frame_cnt, udp_cnt) in packets {
+            tracker.record_udp_cnt(frame_cnt, udp_cnt);
+        }
+        tracker.stats
+    }
+
+    fn counts(stats: PortStats) -> (u64, u64, u64) {
+        (stats.lost, stats.reordered, stats.duplicated)
+    }
+
+    #[test]
+    fn in_order() {
+        assert_eq!(
+            counts(track(&[(0, 0), (0, 1), (0, 2), (1, 0), (1, 1)])),
+            (0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn gap() {
+        assert_eq!(counts(track(&[(0, 0), (0, 3), (0, 4)])), (2, 0, 0));
+    }
+
+    #[test]
+    fn gap_at_the_frame_start() {
+        // The udp_cnt is cleared at the beginning of the frame 1.
+        assert_eq!(
+            counts(track(&[(0, 0), (0, 1), (0, 2), (0, 3), (1, 2)])),
+            (2, 0, 0)
+        );
+    }
+
+    #[test]
+    fn reordered_in_the_frame() {
+        assert_eq!(counts(track(&[(0, 0), (0, 2), (0, 1), (0, 3)])), (0, 1, 0));
+    }
+
+    #[test]
+    fn duplicated_in_the_frame() {
+        assert_eq!(counts(track(&[(0, 0), (0, 1), (0, 1), (0, 0)])), (0, 0, 2));
+    }
+
+    #[test]
+    fn late_packet_of_the_cleared_previous_frame() {
+        // The packet 1 of the frame 0 is counted as lost, then arrives after the frame 1 starts.
+        assert_eq!(counts(track(&[(0, 0), (0, 2), (1, 0), (0, 1)])), (0, 1, 0));
+    }
+
+    #[test]
+    fn duplicated_packet_of_the_cleared_previous_frame() {
+        assert_eq!(counts(track(&[(0, 0), (0, 1), (1, 0), (0, 1)])), (0, 0, 1));
+    }
+
+    #[test]
+    fn late_packet_of_the_continuous_previous_frame() {
+        // The udp_cnt keeps counting across the frames.
+        assert_eq!(
+            counts(track(&[(0, 100), (0, 101), (1, 103), (0, 102), (0, 102)])),
+            (0, 1, 1)
+        );
+    }
+
+    #[test]
+    fn late_tail_of_the_cleared_previous_frame() {
+        // The tail of the frame 0 has never been counted as lost.
+        assert_eq!(counts(track(&[(0, 0), (0, 1), (1, 0), (0, 2)])), (0, 1, 0));
+    }
+
+    #[test]
+    fn out_of_the_window() {
+        let late = PortStats::REORDER_WINDOW + 2;
+        assert_eq!(
+            counts(track(&[(0, 0), (0, late), (0, 1)])),
+            (late as u64 - 1, 1, 0)
+        );
+    }
+}
@@ -0,0 +1,473 @@
+//! The io_uring backed point cloud port, only available on Linux with the `io-uring` feature.
+//!
+//! The datagrams are received by a multishot `recv` into a registered buffer ring,
+//! so there is no syscall per packet as long as the packets keep coming.
+use std::{
+    alloc::{self, Layout},
+    io,
+    net::{SocketAddr, UdpSocket},
+    os::fd::{AsRawFd, BorrowedFd},
+    ptr::NonNull,
+    sync::atomic::{AtomicU16, Ordering},
+};
+
+use io_uring::{IoUring, cqueue, opcode, types};
+
+#[cfg(feature = "async-net")]
+use crate::runtime::AsyncNet;
+use crate::runtime::{AsyncFd, FdRuntime};
+
+use super::{
+    CrcCheck, PortStats, SocketPortConfig, point_data::PointPacketRef, stats::StatsTracker,
+};
+
+/// The buffer group id of the registered buffer ring.
+const BUF_GROUP: u16 = 0;
+/// Number of the submission queue entries, only the multishot recv is submitted.
+const SQ_ENTRIES: u32 = 8;
+
+/// The point cloud port receiving packets by io_uring.
+///
+/// Same as the [`PointDataPort`](super::PointDataPort),
+/// the packet returned by [`next_packet_ref`](Self::next_packet_ref) borrows the received buffer without copying.
+pub struct UringPointDataPort<R: FdRuntime> {
+    ring: IoUring,
+    /// Duplicated fd of the `ring`, which is readable when there are completions.
+    ring_fd: R::AsyncFd,
+    buf_ring: BufRing,
+    /// The buffer handed out by the last [`next_packet_ref`](Self::next_packet_ref).
+    in_use: Option<u16>,
+    socket: UdpSocket,
+    crc_check: CrcCheck,
+    stats: StatsTracker,
+}
+
+#[cfg(feature = "async-net")]
+impl UringPointDataPort<AsyncNet> {
+    /// Creates the port on [`AsyncNet`], see also [`new_with_runtime`](Self::new_with_runtime).
+    pub fn new(
+        local_addr: impl Into<SocketAddr>,
+        lidar_addr: impl Into<SocketAddr>,
+        buffer_count: u16,
+        buffer_size: usize,
+    ) -> crate::Result<Self> {
+        Self::new_with_runtime(local_addr, lidar_addr, buffer_count, buffer_size)
+    }
+}
+
+impl<R: FdRuntime> UringPointDataPort<R> {
+    const DEFAULT_BUFFER_SIZE: usize = 1380;
+    const DEFAULT_BUFFER_COUNT: u16 = 512;
+
+    /// Max number of the buffers in a buffer ring.
+    pub const MAX_BUFFER_COUNT: u16 = 32768;
+
+    /// `buffer_count` must be a power of 2, and no more than [`MAX_BUFFER_COUNT`](Self::MAX_BUFFER_COUNT).
+    /// The datagrams longer than `buffer_size` are reported as [`Error::Truncated`](crate::Error::Truncated).
+    ///
+    /// With the [`Tokio`](crate::runtime::Tokio) runtime, it must be called within a tokio runtime.
+    ///
+    /// # Error
+    ///
+    /// Returns an error of [`InvalidInput`](io::ErrorKind::InvalidInput) kind
+    /// if `buffer_count` is invalid or `buffer_size` is zero.
+    pub fn new_with_runtime(
+        local_addr: impl Into<SocketAddr>,
+        lidar_addr: impl Into<SocketAddr>,
+        buffer_count: u16,
+        buffer_size: usize,
+    ) -> crate::Result<Self> {
+        if !(1..=Self::MAX_BUFFER_COUNT).contains(&buffer_count) {
+            return Err(crate::Error::OutOfRange {
+                name: "buffer_count",
+                value: buffer_count.into(),
+                range: 1..=Self::MAX_BUFFER_COUNT.into(),
+            });
+        }
+        if !buffer_count.is_power_of_two() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "buffer_count must be a power of 2",
+            )
+            .into());
+        }
+        // The length of a provided buffer is an u32.
+        if !(1..=u32::MAX as usize).contains(&buffer_size) {
+            return Err(crate::Error::OutOfRange {
+                name: "buffer_size",
+                value: buffer_size as u64,
+                range: 1..=u32::MAX.into(),
+            });
+        }
+        let socket = UdpSocket::bind(local_addr.into())?;
+        socket.connect(lidar_addr.into())?;
+
+        // Every buffer may complete before being consumed,
+        // and the completion queue is no smaller than the submission queue.
+        let ring = IoUring::builder()
+            .setup_cqsize(u32::from(buffer_count).max(SQ_ENTRIES))
+            .build(SQ_ENTRIES)?;
+        // Safety: the fd of the `ring` is open during the call.
+        let ring_fd = unsafe { BorrowedFd::borrow_raw(ring.as_raw_fd()) };
+        let ring_fd = R::AsyncFd::new(ring_fd.try_clone_to_owned()?)?;
+        let buf_ring = BufRing::new(buffer_count, buffer_size);
+        // Safety: the ring memory lives until it is unregistered on drop.
+        unsafe {
+            ring.submitter().register_buf_ring_with_flags(
+                buf_ring.ring.as_ptr() as u64,
+                buffer_count,
+                BUF_GROUP,
+                0,
+            )?;
+        }
+
+        let mut port = Self {
+            ring,
+            ring_fd,
+            buf_ring,
+            in_use: None,
+            socket,
+            crc_check: CrcCheck::default(),
+            stats: StatsTracker::default(),
+        };
+        port.submit_recv()?;
+        Ok(port)
+    }
+
+    /// Sets how the received packets are verified, see also [`CrcCheck`].
+    pub fn with_crc_check(mut self, crc_check: CrcCheck) -> Self {
+        self.crc_check = crc_check;
+        self
+    }
+
+    /// Number of the packets failing the CRC verification so far.
+    pub fn crc_failures(&self) -> u64 {
+        self.stats.snapshot().crc_failures
+    }
+
+    /// Returns a snapshot of the counters since the port was created.
+    pub fn stats(&self) -> PortStats {
+        self.stats.snapshot()
+    }
+
+    pub async fn next_packet_ref(&mut self) -> crate::Result<PointPacketRef<'_>> {
+        if let Some(bid) = self.in_use.take() {
+            self.buf_ring.push(bid);
+        }
+
+        let (bid, len) = loop {
+            let (bid, len) = self.next_completion().await?;
+            // The datagram has been truncated to the buffer, since the recv is with MSG_TRUNC.
+            if len > self.buf_ring.buffer_size {
+                self.buf_ring.push(bid);
+                let truncated = crate::Error::Truncated {
+                    expected: len,
+                    found: self.buf_ring.buffer_size,
+                };
+                return self
+                    .stats
+                    .record_parsed(Err(truncated), |packet: &PointPacketRef| packet.header);
+            }
+            let packet = self.buf_ring.buffer(bid, len);
+            match self.stats.accept(self.crc_check, packet) {
+                Ok(true) => break (bid, len),
+                Ok(false) => self.buf_ring.push(bid),
+                Err(err) => {
+                    self.buf_ring.push(bid);
+                    return Err(err);
+                }
+            }
+        };
+        self.in_use = Some(bid);
+
+        self.stats.record_parsed(
+            PointPacketRef::try_from_bytes(self.buf_ring.buffer(bid, len)),
+            |packet| packet.header,
+        )
+    }
+
+    /// Waits for the next received datagram, returns its buffer id and length.
+    async fn next_completion(&mut self) -> crate::Result<(u16, usize)> {
+        loop {
+            let Some(cqe) = self.ring.completion().next() else {
+                let submission = self.ring.submission();
+                let need_enter = submission.cq_overflow() || submission.taskrun();
+                drop(submission);
+                if need_enter {
+                    // Flushes the overflowed completions and the pending task work.
+                    self.ring.submit()?;
+                } else {
+                    self.ring_fd.readable().await?;
+                }
+                continue;
+            };
+            let (result, flags) = (cqe.result(), cqe.flags());
+            if !cqueue::more(flags) {
+                self.submit_recv()?;
+            }
+            match result {
+                // The buffers run out, the recv has been resubmitted above.
+                result if result == -libc::ENOBUFS => continue,
+                result if result < 0 => return Err(io::Error::from_raw_os_error(-result).into()),
+                len => {
+                    let bid = cqueue::buffer_select(flags).ok_or_else(|| {
+                        io::Error::other("io_uring completion without a selected buffer")
+                    })?;
+                    return Ok((bid, len as usize));
+                }
+            }
+        }
+    }
+
+    /// Submits a multishot recv, which keeps receiving until the buffers run out.
+    ///
+    /// The completion of a truncated datagram has its real length, thanks to the MSG_TRUNC.
+    fn submit_recv(&mut self) -> crate::Result<()> {
+        let recv = opcode::RecvMulti::new(types::Fd(self.socket.as_raw_fd()), BUF_GROUP)
+            .flags(libc::MSG_TRUNC)
+            .build();
+        // Safety: the socket and the buffer ring outlive the io_uring.
+        unsafe {
+            self.ring
+                .submission()
+                .push(&recv)
+                .map_err(|_| io::Error::other("io_uring submission queue is full"))?;
+        }
+        self.ring.submit()?;
+        Ok(())
+    }
+}
+
+impl<R: FdRuntime> Drop for UringPointDataPort<R> {
+    fn drop(&mut self) {
+        let _ = self.ring.submitter().unregister_buf_ring(BUF_GROUP);
+    }
+}
+
+/// The provided buffer ring shared with the kernel.
+struct BufRing {
+    ring: NonNull<types::BufRingEntry>,
+    layout: Layout,
+    mask: u16,
+    tail: u16,
+    buffers: Box<[u8]>,
+    buffer_size: usize,
+}
+
+// Safety: the ring memory is exclusively owned by `BufRing`.
+unsafe impl Send for BufRing {}
+unsafe impl Sync for BufRing {}
+
+impl BufRing {
+    fn new(count: u16, buffer_size: usize) -> Self {
+        let layout = Layout::array::<types::BufRingEntry>(count as usize)
+            .and_then(|layout| layout.align_to(4096))
+            .expect("buffer ring layout overflow");
+        // Safety: the layout is not zero-sized since `count` is a power of 2.
+        let ring = unsafe { alloc::alloc_zeroed(layout) };
+        let ring = NonNull::new(ring.cast()).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+
+        let mut buf_ring = Self {
+            ring,
+            layout,
+            mask: count - 1,
+            tail: 0,
+            buffers: vec![0; count as usize * buffer_size].into_boxed_slice(),
+            buffer_size,
+        };
+        (0..count).for_each(|bid| buf_ring.push(bid));
+        buf_ring
+    }
+
+    /// Gives the buffer back to the kernel.
+    fn push(&mut self, bid: u16) {
+        let addr = self.buffers[bid as usize * self.buffer_size..].as_ptr();
+        // Safety: the index is masked within the ring.
+        let entry = unsafe { &mut *self.ring.as_ptr().add((self.tail & self.mask) as usize) };
+        entry.set_addr(addr as u64);
+        entry.set_len(self.buffer_size as u32);
+        entry.set_bid(bid);
+
+        self.tail = self.tail.wrapping_add(1);
+        // Safety: the tail is shared with the kernel, which only reads it.
+        let tail = unsafe {
+            AtomicU16::from_ptr(types::BufRingEntry::tail(self.ring.as_ptr()).cast_mut())
+        };
+        tail.store(self.tail, Ordering::Release);
+    }
+
+    /// The received `len` must be no more than the `buffer_size`.
+    fn buffer(&self, bid: u16, len: usize) -> &[u8] {
+        debug_assert!(len <= self.buffer_size);
+        let start = bid as usize * self.buffer_size;
+        &self.buffers[start..start + len]
+    }
+}
+
+impl Drop for BufRing {
+    fn drop(&mut self) {
+        // Safety: allocated with the same layout.
+        unsafe { alloc::dealloc(self.ring.as_ptr().cast(), self.layout) };
+    }
+}
+
+impl super::IpConfig {
+    #[cfg(feature = "async-net")]
+    pub fn new_uring_point_data_port(
+        &self,
+        point_data_port: &SocketPortConfig,
+        buffer_count: u16,
+        buffer_size: usize,
+    ) -> crate::Result<UringPointDataPort<AsyncNet>> {
+        self.new_uring_point_data_port_with_runtime(point_data_port, buffer_count, buffer_size)
+    }
+    #[cfg(feature = "async-net")]
+    pub fn new_default_uring_point_data_port(&self) -> crate::Result<UringPointDataPort<AsyncNet>> {
+        self.new_default_uring_point_data_port_with_runtime()
+    }
+
+    /// Same as [`new_uring_point_data_port`](Self::new_uring_point_data_port), but on the given [`FdRuntime`].
+    pub fn new_uring_point_data_port_with_runtime<R: FdRuntime>(
+        &self,
+        point_data_port: &SocketPortConfig,
+        buffer_count: u16,
+        buffer_size: usize,
+    ) -> crate::Result<UringPointDataPort<R>> {
+        UringPointDataPort::new_with_runtime(
+            (self.local, point_data_port.local),
+            (self.lidar, point_data_port.lidar),
+            buffer_count,
+            buffer_size,
+        )
+    }
+    pub fn new_default_uring_point_data_port_with_runtime<R: FdRuntime>(
+        &self,
+    ) -> crate::Result<UringPointDataPort<R>> {
+        self.new_uring_point_data_port_with_runtime(
+            &SocketPortConfig::new_point_data_config(),
+            UringPointDataPort::<R>::DEFAULT_BUFFER_COUNT,
+            UringPointDataPort::<R>::DEFAULT_BUFFER_SIZE,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::UdpSocket as StdUdpSocket, time::Duration};
+
+    use super::*;
+    use crate::runtime;
+
+    /// A packet of one cartesian high point.
+    fn packet(udp_cnt: u16) -> Vec<u8> {
+        const LEN: u16 = 36 + 14;
+        let mut packet = vec![0; LEN as usize];
+        packet[1..3].copy_from_slice(&LEN.to_le_bytes());
+        packet[5..7].copy_from_slice(&1u16.to_le_bytes());
+        packet[7..9].copy_from_slice(&udp_cnt.to_le_bytes());
+        packet[10] = 1;
+        packet
+    }
+
+    /// Returns `None` if io_uring is not available, e.g. disabled in a container.
+    fn loopback<R: FdRuntime>(
+        buffer_count: u16,
+        buffer_size: usize,
+    ) -> Option<(UringPointDataPort<R>, StdUdpSocket)> {
+        if IoUring::new(2).is_err() {
+            return None;
+        }
+        let lidar = StdUdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = UringPointDataPort::new_with_runtime(
+            ([127, 0, 0, 1], 0),
+            lidar.local_addr().unwrap(),
+            buffer_count,
+            buffer_size,
+        )
+        .unwrap();
+        lidar.connect(port.socket.local_addr().unwrap()).unwrap();
+        Some((port, lidar))
+    }
+
+    async fn recycle_buffers<R: FdRuntime>() {
+        let Some((mut port, lidar)) = loopback::<R>(2, 64) else {
+            return;
+        };
+        // More packets than the buffers, so the multishot recv runs out of buffers and is resubmitted.
+        for udp_cnt in 0..8 {
+            lidar.send(&packet(udp_cnt)).unwrap();
+        }
+        for udp_cnt in 0..8 {
+            let packet = runtime::timeout::<R, _>(Duration::from_secs(1), port.next_packet_ref())
+                .await
+                .expect("the recv should be resubmitted")
+                .unwrap();
+            assert_eq!({ packet.header.udp_cnt }, udp_cnt);
+            let len = packet.header.length as usize;
+
+            // The buffer in use is not given back until the next call.
+            R::sleep(Duration::from_millis(10)).await;
+            let bid = port.in_use.unwrap();
+            let packet = PointPacketRef::try_from_bytes(port.buf_ring.buffer(bid, len)).unwrap();
+            assert_eq!({ packet.header.udp_cnt }, udp_cnt);
+        }
+    }
+
+    async fn truncated<R: FdRuntime>() {
+        let Some((mut port, lidar)) = loopback::<R>(2, 64) else {
+            return;
+        };
+        lidar.send(&[0; 100]).unwrap();
+        lidar.send(&packet(1)).unwrap();
+        assert!(matches!(
+            port.next_packet_ref().await,
+            Err(crate::Error::Truncated {
+                expected: 100,
+                found: 64
+            })
+        ));
+        let packet = port.next_packet_ref().await.unwrap();
+        assert_eq!({ packet.header.udp_cnt }, 1);
+        assert_eq!(port.stats().parse_errors, 1);
+    }
+
+    #[cfg(feature = "async-net")]
+    #[test]
+    fn recycle_buffers_async_net() {
+        smol::block_on(recycle_buffers::<AsyncNet>());
+    }
+
+    #[cfg(feature = "async-net")]
+    #[test]
+    fn truncated_async_net() {
+        smol::block_on(truncated::<AsyncNet>());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn recycle_buffers_tokio() {
+        recycle_buffers::<crate::runtime::Tokio>().await;
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn truncated_tokio() {
+        truncated::<crate::runtime::Tokio>().await;
+    }
+
+    #[cfg(feature = "async-net")]
+    #[test]
+    fn invalid_buffers() {
+        for (buffer_count, buffer_size) in [(0, 64), (3, 64), (40000, 64), (2, 0)] {
+            let err = UringPointDataPort::new(
+                ([127, 0, 0, 1], 0),
+                ([127, 0, 0, 1], 56300),
+                buffer_count,
+                buffer_size,
+            )
+            .err()
+            .unwrap();
+            assert_eq!(io::Error::from(err).kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+}
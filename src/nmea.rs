@@ -0,0 +1,222 @@
+//! Parsing of the NMEA `RMC` sentences, which feed the GPS time to the lidar,
+//! see [`ControlPort::forward_nmea`](crate::lidar_port::ControlPort::forward_nmea).
+//!
+//! The lidar takes the time of the sentence as the time of the last PPS edge,
+//! so only the sentences at a whole second should be forwarded.
+use std::fmt::Display;
+
+/// A valid `RMC` sentence (recommended minimum data), e.g. `$GPRMC` or `$GNRMC`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rmc {
+    sentence: String,
+    /// Nanoseconds since the Unix epoch.
+    utc_nanos: u64,
+}
+
+impl Rmc {
+    /// Parses a sentence, the trailing line break is ignored.
+    ///
+    /// Returns `None` if the sentence is not an `RMC`, its checksum mismatches,
+    /// its status is not `A` (valid), or its time or date is malformed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use livox2::nmea::Rmc;
+    /// let rmc = Rmc::parse("$GPRMC,083559.00,A,4717.11437,N,00833.91522,E,0.004,77.52,091202,,,A*57\r\n").unwrap();
+    /// assert_eq!(rmc.utc_nanos(), 1_039_422_959_000_000_000);
+    /// assert!(rmc.is_pps_aligned());
+    /// ```
+    pub fn parse(sentence: &str) -> Option<Self> {
+        let sentence = sentence.trim_end();
+        let (body, checksum) = sentence.strip_prefix('$')?.split_once('*')?;
+        let checksum = u8::from_str_radix(checksum, 16).ok()?;
+        if body.bytes().fold(0, |sum, byte| sum ^ byte) != checksum {
+            return None;
+        }
+
+        let mut fields = body.split(',');
+        let address = fields.next()?;
+        if address.len() != 5 || !address.ends_with("RMC") {
+            return None;
+        }
+        let time = fields.next()?;
+        if fields.next()? != "A" {
+            return None;
+        }
+        // Skips the latitude, the longitude, the speed and the course.
+        let date = fields.nth(6)?;
+
+        Some(Self {
+            sentence: sentence.to_owned(),
+            utc_nanos: parse_utc_nanos(time, date)?,
+        })
+    }
+
+    /// The sentence without the trailing line break.
+    pub fn sentence(&self) -> &str {
+        &self.sentence
+    }
+
+    /// The UTC time of the sentence, in nanoseconds since the Unix epoch.
+    pub const fn utc_nanos(&self) -> u64 {
+        self.utc_nanos
+    }
+
+    /// Whether the time is at a whole second, i.e. aligned to a PPS edge.
+    pub const fn is_pps_aligned(&self) -> bool {
+        self.utc_nanos.is_multiple_of(NANOS_PER_SEC)
+    }
+}
+
+impl Display for Rmc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.sentence)
+    }
+}
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// Parses the `hhmmss.sss` time and the `ddmmyy` date, the year is in 2000-2099.
+fn parse_utc_nanos(time: &str, date: &str) -> Option<u64> {
+    let (hms, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let [hour, minute, second] = parse_pairs(hms)?;
+    let [day, month, year] = parse_pairs(date)?;
+    if hour > 23 || minute > 59 || second > 60 || !(1..=12).contains(&month) {
+        return None;
+    }
+    let year = 2000 + year;
+    if !(1..=days_in_month(year, month)).contains(&day) {
+        return None;
+    }
+
+    if fraction.len() > 9 || !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let nanos = match fraction {
+        "" => 0,
+        fraction => fraction.parse::<u64>().ok()? * 10u64.pow(9 - fraction.len() as u32),
+    };
+
+    let days = days_from_civil(year, month, day);
+    let secs = ((days * 24 + hour) * 60 + minute) * 60 + second;
+    Some(secs * NANOS_PER_SEC + nanos)
+}
+
+/// Parses 3 pairs of decimal digits.
+fn parse_pairs(digits: &str) -> Option<[u64; 3]> {
+    let bytes = digits.as_bytes();
+    if bytes.len() != 6 || !bytes.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    Some(std::array::from_fn(|i| {
+        ((bytes[2 * i] - b'0') * 10 + bytes[2 * i + 1] - b'0') as u64
+    }))
+}
+
+const fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since the Unix epoch of a date after it,
+/// see also [`days_from_civil`](https://howardhinnant.github.io/date_algorithms.html#days_from_civil).
+const fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Appends the checksum to the sentence body.
+    fn sentence(body: &str) -> String {
+        let checksum = body.bytes().fold(0, |sum, byte| sum ^ byte);
+        format!("${body}*{checksum:02X}\r\n")
+    }
+
+    fn rmc_sentence(time: &str, status: &str, date: &str) -> String {
+        sentence(&format!(
+            "GNRMC,{time},{status},4717.11437,N,00833.91522,E,0.004,77.52,{date},,,A"
+        ))
+    }
+
+    #[test]
+    fn valid() {
+        let rmc = Rmc::parse(&rmc_sentence("235960", "A", "311224")).unwrap();
+        // The leap second.
+        assert_eq!(rmc.utc_nanos(), 1_735_689_600 * NANOS_PER_SEC);
+        assert!(rmc.is_pps_aligned());
+        assert_eq!(rmc.sentence(), rmc.to_string());
+
+        let leap_day = Rmc::parse(&rmc_sentence("000000", "A", "290224")).unwrap();
+        assert_eq!(leap_day.utc_nanos(), 1_709_164_800 * NANOS_PER_SEC);
+        assert!(Rmc::parse(&rmc_sentence("000000", "A", "290200")).is_some());
+    }
+
+    #[test]
+    fn bad_checksum() {
+        let mut bad = rmc_sentence("083559.00", "A", "091202");
+        bad.replace_range(bad.len() - 4..bad.len() - 2, "00");
+        assert!(Rmc::parse(&bad).is_none());
+        assert!(Rmc::parse(bad.replace('*', ",").as_str()).is_none());
+    }
+
+    #[test]
+    fn invalid_status() {
+        assert!(Rmc::parse(&rmc_sentence("083559.00", "V", "091202")).is_none());
+    }
+
+    #[test]
+    fn not_rmc() {
+        let gga = sentence("GPGGA,083559.00,4717.11437,N,00833.91522,E,1,08,1.01,499.6,M,48.0,M,,");
+        assert!(Rmc::parse(&gga).is_none());
+    }
+
+    #[test]
+    fn malformed_date() {
+        for date in [
+            "450299", "300224", "290223", "310424", "001224", "011324", "1224", "ab1224",
+        ] {
+            assert!(
+                Rmc::parse(&rmc_sentence("083559", "A", date)).is_none(),
+                "{date}"
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_time() {
+        for time in [
+            "240000",
+            "236000",
+            "083561",
+            "0835",
+            "083559.1234567890",
+            "083559.-1",
+        ] {
+            assert!(
+                Rmc::parse(&rmc_sentence(time, "A", "091202")).is_none(),
+                "{time}"
+            );
+        }
+    }
+
+    #[test]
+    fn not_whole_second() {
+        let rmc = Rmc::parse(&rmc_sentence("083559.25", "A", "091202")).unwrap();
+        assert_eq!(rmc.utc_nanos() % NANOS_PER_SEC, 250_000_000);
+        assert!(!rmc.is_pps_aligned());
+    }
+}
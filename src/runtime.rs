@@ -0,0 +1,269 @@
+//! The async runtimes which the lidar ports can run on.
+//!
+//! All the ports are generic over a [`Runtime`], which is always chosen explicitly:
+//! - The constructors without a suffix, e.g. `PointDataPort::new`, run on [`AsyncNet`],
+//!   which requires the `async-net` feature, the default feature.
+//! - The constructors with the `_with_runtime` suffix run on the given runtime,
+//!   e.g. `PointDataPort::<Tokio>::new_with_runtime` with the `tokio` feature.
+//!
+//! Disable the default features to use tokio without a second reactor.
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+use std::os::fd::OwnedFd;
+use std::{future::Future, io, net::SocketAddr, time::Duration};
+
+/// An async runtime providing the UDP socket and the timer.
+pub trait Runtime: Send + Sync + 'static {
+    type UdpSocket: UdpSocket;
+
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send;
+}
+
+/// The UDP socket of a [`Runtime`].
+pub trait UdpSocket: Sized + Send + Sync + 'static {
+    fn bind(addr: SocketAddr) -> impl Future<Output = io::Result<Self>> + Send;
+
+    fn connect(&self, addr: SocketAddr) -> impl Future<Output = io::Result<()>> + Send;
+
+    fn send(&self, buf: &[u8]) -> impl Future<Output = io::Result<usize>> + Send;
+
+    fn send_to(
+        &self,
+        buf: &[u8],
+        addr: SocketAddr,
+    ) -> impl Future<Output = io::Result<usize>> + Send;
+
+    fn recv(&self, buf: &mut [u8]) -> impl Future<Output = io::Result<usize>> + Send;
+
+    fn recv_from(
+        &self,
+        buf: &mut [u8],
+    ) -> impl Future<Output = io::Result<(usize, SocketAddr)>> + Send;
+
+    /// Waits until the socket is readable and calls the non-blocking `op` on it,
+    /// the `op` is retried if it returns [`WouldBlock`](io::ErrorKind::WouldBlock).
+    fn read_with<T: Send>(
+        &self,
+        op: impl FnMut() -> io::Result<T> + Send,
+    ) -> impl Future<Output = io::Result<T>> + Send;
+
+    fn set_broadcast(&self, on: bool) -> io::Result<()>;
+
+    fn local_addr(&self) -> io::Result<SocketAddr>;
+
+    fn peer_addr(&self) -> io::Result<SocketAddr>;
+}
+
+/// A [`Runtime`] able to wait for the readiness of a file descriptor, used by the io_uring port.
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+pub trait FdRuntime: Runtime {
+    type AsyncFd: AsyncFd;
+}
+
+/// A file descriptor registered in the reactor of a [`FdRuntime`].
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+pub trait AsyncFd: Sized + Send + Sync + 'static {
+    fn new(fd: OwnedFd) -> io::Result<Self>;
+
+    /// Waits until the fd is readable.
+    fn readable(&self) -> impl Future<Output = io::Result<()>> + Send;
+}
+
+/// Returns `None` if the future does not complete within the duration.
+pub(crate) async fn timeout<R: Runtime, T>(
+    duration: Duration,
+    future: impl Future<Output = T>,
+) -> Option<T> {
+    futures_lite::future::or(async { Some(future.await) }, async {
+        R::sleep(duration).await;
+        None
+    })
+    .await
+}
+
+/// Binds a socket to the `local_addr` and connects it to the `lidar_addr`.
+pub(crate) async fn connect<R: Runtime>(
+    local_addr: SocketAddr,
+    lidar_addr: SocketAddr,
+) -> io::Result<R::UdpSocket> {
+    let socket = R::UdpSocket::bind(local_addr).await?;
+    socket.connect(lidar_addr).await?;
+    Ok(socket)
+}
+
+/// The runtime based on [`async_net`] and [`async_io`], which works with any executor.
+#[cfg(feature = "async-net")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsyncNet;
+
+#[cfg(feature = "async-net")]
+const _: () = {
+    impl Runtime for AsyncNet {
+        type UdpSocket = async_net::UdpSocket;
+
+        async fn sleep(duration: Duration) {
+            async_io::Timer::after(duration).await;
+        }
+    }
+
+    impl UdpSocket for async_net::UdpSocket {
+        fn bind(addr: SocketAddr) -> impl Future<Output = io::Result<Self>> + Send {
+            async_net::UdpSocket::bind(addr)
+        }
+
+        fn connect(&self, addr: SocketAddr) -> impl Future<Output = io::Result<()>> + Send {
+            self.connect(addr)
+        }
+
+        fn send(&self, buf: &[u8]) -> impl Future<Output = io::Result<usize>> + Send {
+            self.send(buf)
+        }
+
+        fn send_to(
+            &self,
+            buf: &[u8],
+            addr: SocketAddr,
+        ) -> impl Future<Output = io::Result<usize>> + Send {
+            self.send_to(buf, addr)
+        }
+
+        fn recv(&self, buf: &mut [u8]) -> impl Future<Output = io::Result<usize>> + Send {
+            self.recv(buf)
+        }
+
+        fn recv_from(
+            &self,
+            buf: &mut [u8],
+        ) -> impl Future<Output = io::Result<(usize, SocketAddr)>> + Send {
+            self.recv_from(buf)
+        }
+
+        async fn read_with<T: Send>(
+            &self,
+            mut op: impl FnMut() -> io::Result<T> + Send,
+        ) -> io::Result<T> {
+            loop {
+                // A datagram is queued once the empty peek returns.
+                self.peek(&mut []).await?;
+                match op() {
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => continue,
+                    result => return result,
+                }
+            }
+        }
+
+        fn set_broadcast(&self, on: bool) -> io::Result<()> {
+            self.set_broadcast(on)
+        }
+
+        fn local_addr(&self) -> io::Result<SocketAddr> {
+            self.local_addr()
+        }
+
+        fn peer_addr(&self) -> io::Result<SocketAddr> {
+            self.peer_addr()
+        }
+    }
+
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    impl FdRuntime for AsyncNet {
+        type AsyncFd = async_io::Async<OwnedFd>;
+    }
+
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    impl AsyncFd for async_io::Async<OwnedFd> {
+        fn new(fd: OwnedFd) -> io::Result<Self> {
+            async_io::Async::new(fd)
+        }
+
+        fn readable(&self) -> impl Future<Output = io::Result<()>> + Send {
+            self.readable()
+        }
+    }
+};
+
+/// The runtime based on [`tokio`], the ports must be used within a tokio runtime
+/// with both IO and time drivers enabled.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tokio;
+
+#[cfg(feature = "tokio")]
+const _: () = {
+    impl Runtime for Tokio {
+        type UdpSocket = tokio::net::UdpSocket;
+
+        fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+            tokio::time::sleep(duration)
+        }
+    }
+
+    impl UdpSocket for tokio::net::UdpSocket {
+        fn bind(addr: SocketAddr) -> impl Future<Output = io::Result<Self>> + Send {
+            tokio::net::UdpSocket::bind(addr)
+        }
+
+        fn connect(&self, addr: SocketAddr) -> impl Future<Output = io::Result<()>> + Send {
+            self.connect(addr)
+        }
+
+        fn send(&self, buf: &[u8]) -> impl Future<Output = io::Result<usize>> + Send {
+            self.send(buf)
+        }
+
+        fn send_to(
+            &self,
+            buf: &[u8],
+            addr: SocketAddr,
+        ) -> impl Future<Output = io::Result<usize>> + Send {
+            self.send_to(buf, addr)
+        }
+
+        fn recv(&self, buf: &mut [u8]) -> impl Future<Output = io::Result<usize>> + Send {
+            self.recv(buf)
+        }
+
+        fn recv_from(
+            &self,
+            buf: &mut [u8],
+        ) -> impl Future<Output = io::Result<(usize, SocketAddr)>> + Send {
+            self.recv_from(buf)
+        }
+
+        async fn read_with<T: Send>(
+            &self,
+            mut op: impl FnMut() -> io::Result<T> + Send,
+        ) -> io::Result<T> {
+            self.async_io(tokio::io::Interest::READABLE, &mut op).await
+        }
+
+        fn set_broadcast(&self, on: bool) -> io::Result<()> {
+            self.set_broadcast(on)
+        }
+
+        fn local_addr(&self) -> io::Result<SocketAddr> {
+            self.local_addr()
+        }
+
+        fn peer_addr(&self) -> io::Result<SocketAddr> {
+            self.peer_addr()
+        }
+    }
+
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    impl FdRuntime for Tokio {
+        type AsyncFd = tokio::io::unix::AsyncFd<OwnedFd>;
+    }
+
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    impl AsyncFd for tokio::io::unix::AsyncFd<OwnedFd> {
+        fn new(fd: OwnedFd) -> io::Result<Self> {
+            tokio::io::unix::AsyncFd::with_interest(fd, tokio::io::Interest::READABLE)
+        }
+
+        async fn readable(&self) -> io::Result<()> {
+            // The readiness is edge triggered, which is set again by the next completion.
+            self.readable().await?.clear_ready();
+            Ok(())
+        }
+    }
+};
@@ -1,10 +1,14 @@
 //! Many data structures used to communicate with the livox lidar device.
 pub mod ethernet;
+pub mod key_value;
+pub mod point;
 pub mod sdk_packet;
+pub mod soa;
+pub mod state_info;
 
 use core::ffi;
 
-use zerocopy::{Immutable, KnownLayout};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
 
 #[derive(KnownLayout, Immutable)]
 #[repr(u8)]
@@ -50,6 +54,17 @@ pub struct AsyncControlResponse {
     pub error_key: u16,
 }
 
+impl AsyncControlResponse {
+    pub fn return_code(&self) -> sdk_packet::ReturnCode {
+        self.ret_code.into()
+    }
+
+    /// Turns a non-zero return code of the `cmd_id` into [`Error::CommandFailed`](crate::Error::CommandFailed).
+    pub fn check(&self, cmd_id: sdk_packet::CommandID) -> Result<(), crate::Error> {
+        self.return_code().check(cmd_id, Some(self.error_key))
+    }
+}
+
 #[repr(C)]
 pub struct InfoResponse {
     pub ret_code: u8,
@@ -75,7 +90,6 @@ pub enum WorkModeAfterBoot {
     AfterBootWakeUp = 0x02,
 }
 
-
 pub struct DirectLidarStateInfo {
     pub pcl_data_type: u8,                      // 0x0000
     pub pattern_mode: u8,                       // 0x0001
@@ -187,6 +201,8 @@ pub struct LivoxLidarStateInfo {
     pub status_code: u64,
 }
 
+#[derive(Debug, Clone, Copy, KnownLayout, Immutable, Unaligned, FromBytes, IntoBytes)]
+#[repr(C, packed)]
 pub struct LivoxLidarInstallAttitude {
     pub roll_deg: f32,
     pub pitch_deg: f32,
@@ -199,6 +215,8 @@ pub struct LivoxLidarInstallAttitude {
     pub z: i32,
 }
 
+#[derive(Debug, Clone, Copy, KnownLayout, Immutable, Unaligned, FromBytes, IntoBytes)]
+#[repr(C, packed)]
 pub struct FovCfg {
     pub yaw_start: i32,
     pub yaw_stop: i32,
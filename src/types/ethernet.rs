@@ -1,7 +1,9 @@
-use std::fmt::Display;
+use std::{fmt::Display, mem::offset_of};
 
 use zerocopy::{Immutable, IntoBytes, KnownLayout, TryFromBytes, Unaligned};
 
+use crate::error::{EnumField, EnumFields};
+
 /// see also: [`Livox Ethernet Protocol`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html#point-cloud-imu-data-protocol)
 #[derive(Debug, KnownLayout, Immutable, Unaligned, TryFromBytes)]
 #[repr(C, packed)]
@@ -98,6 +100,8 @@ pub struct ImuData {
     pub acc_z: f32,
 }
 
+impl EnumFields for ImuData {}
+
 /// Cartesian coordinate data with high precision.
 #[derive(Debug, Clone, KnownLayout, Immutable, Unaligned, TryFromBytes, IntoBytes)]
 #[repr(C, packed)]
@@ -110,7 +114,8 @@ pub struct CartesianHighPoint {
     pub z: i32,
     pub reflectivity: u8,
     /// According to the point cloud frame header `pack_info.tag_type` field, which is in [`reserved`](EthernetPacketHeader::reserved) field, match the specific tag type.
-    /// For details, see [`2.4 Tag Information`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html#tag-information).
+    /// For details, see [`2.4 Tag Information`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html#tag-information),
+    /// which can be decoded by [`PointTag`](super::point::PointTag).
     pub tag: u8,
 }
 
@@ -126,7 +131,8 @@ pub struct CartesianLowPoint {
     pub z: i16,
     pub reflectivity: u8,
     /// According to the point cloud frame header `pack_info.tag_type` field, which is in [`reserved`](EthernetPacketHeader::reserved) field, match the specific tag type.
-    /// For details, see [`2.4 Tag Information`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html#tag-information).
+    /// For details, see [`2.4 Tag Information`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html#tag-information),
+    /// which can be decoded by [`PointTag`](super::point::PointTag).
     pub tag: u8,
 }
 
@@ -142,11 +148,47 @@ pub struct SphericalPoint {
     pub phi: u16,
     pub reflectivity: u8,
     /// According to the point cloud frame header `pack_info.tag_type` field, which is in [`reserved`](EthernetPacketHeader::reserved) field, match the specific tag type.
-    /// For details, see [`2.4 Tag Information`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html#tag-information).
+    /// For details, see [`2.4 Tag Information`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html#tag-information),
+    /// which can be decoded by [`PointTag`](super::point::PointTag).
     pub tag: u8,
 }
 
+impl EnumFields for EthernetPacketHeader {
+    const ENUM_FIELDS: &[EnumField] = &[
+        EnumField::new::<PointDataType>(offset_of!(Self, data_type)),
+        EnumField::new::<TimestampType>(offset_of!(Self, time_type)),
+    ];
+}
+
 impl EthernetPacketHeader {
+    /// Offset of the [`timestamp`](Self::timestamp), where the [`crc32`](Self::crc32) starts from.
+    const CRC32_OFFSET: usize = offset_of!(Self, timestamp);
+
+    /// Verifies the [`crc32`](Self::crc32) over the timestamp and the data segment of the `packet`,
+    /// which is the whole datagram starting with the header.
+    ///
+    /// # Error
+    ///
+    /// Returns [`Error::CrcMismatch`](crate::Error::CrcMismatch) if the checksum does not match.
+    pub fn verify_crc32(packet: &[u8]) -> Result<(), crate::Error> {
+        let (header, _) = Self::try_ref_from_prefix(packet)?;
+        let (expected, length) = (header.crc32, header.length as usize);
+        let checked = packet
+            .get(Self::CRC32_OFFSET..length)
+            .ok_or(crate::Error::Truncated {
+                expected: length,
+                found: packet.len(),
+            })?;
+        let calculated = crate::crc::CRC32.checksum(checked);
+        if calculated != expected {
+            return Err(crate::Error::CrcMismatch {
+                expected,
+                calculated,
+            });
+        }
+        Ok(())
+    }
+
     pub const fn timestamp_sec(&self) -> f64 {
         self.timestamp as f64 * 1e-9
     }
@@ -162,22 +204,83 @@ impl EthernetPacketHeader {
     pub const fn timestamp_sec_range(&self) -> std::ops::Range<f64> {
         self.timestamp_sec()..self.end_timestamp_sec()
     }
+
+    /// The [`time_interval`](Self::time_interval) in ns.
+    pub const fn time_interval_ns(&self) -> u64 {
+        self.time_interval as u64 * 100
+    }
+
+    /// Timestamp of the `index`-th point interpolated over the [`time_interval`](Self::time_interval),
+    /// which is `timestamp + time_interval * index / (dot_num - 1)`, Unit: ns
+    pub const fn point_timestamp(&self, index: usize) -> u64 {
+        interpolate_timestamp(
+            self.timestamp,
+            self.time_interval_ns(),
+            self.dot_num as usize,
+            index,
+        )
+    }
 }
 
+/// Same as [`EthernetPacketHeader::point_timestamp`], Unit: ns
+pub(crate) const fn interpolate_timestamp(
+    timestamp: u64,
+    time_interval_ns: u64,
+    dot_num: usize,
+    index: usize,
+) -> u64 {
+    if dot_num <= 1 {
+        return timestamp;
+    }
+    // A corrupted timestamp saturates instead of overflowing.
+    timestamp.saturating_add(time_interval_ns.saturating_mul(index as u64) / (dot_num as u64 - 1))
+}
+
+#[cfg(feature = "fugit")]
+pub(crate) type NanosInstantU64 = fugit::Instant<u64, 1, 1_000_000_000>;
+
 #[cfg(feature = "fugit")]
 const _: () = {
-    type NanosInstantU64 = fugit::Instant<u64, 1, 1_000_000_000>;
     impl EthernetPacketHeader {
         pub const fn timestamp_instant(&self) -> NanosInstantU64 {
             NanosInstantU64::from_ticks(self.timestamp)
         }
 
+        /// The [`time_interval`](Self::time_interval) as a duration, which is converted from 0.1us to ns.
         pub const fn time_interval(&self) -> fugit::NanosDurationU64 {
-            fugit::NanosDurationU64::from_ticks(self.time_interval as u64)
+            fugit::NanosDurationU64::from_ticks(self.time_interval_ns())
+        }
+
+        pub const fn end_time_instant(&self) -> NanosInstantU64 {
+            NanosInstantU64::from_ticks(self.timestamp.saturating_add(self.time_interval_ns()))
         }
 
-        pub fn end_time_instant(&self) -> NanosInstantU64 {
-            self.timestamp_instant() + self.time_interval()
+        /// Same as [`point_timestamp`](Self::point_timestamp), but returns an instant.
+        pub const fn point_instant(&self, index: usize) -> NanosInstantU64 {
+            NanosInstantU64::from_ticks(self.point_timestamp(index))
         }
     }
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolate() {
+        assert_eq!(interpolate_timestamp(1_000, 900, 10, 0), 1_000);
+        assert_eq!(interpolate_timestamp(1_000, 900, 10, 3), 1_300);
+        assert_eq!(interpolate_timestamp(1_000, 900, 10, 9), 1_900);
+        assert_eq!(interpolate_timestamp(1_000, 900, 1, 0), 1_000);
+        assert_eq!(interpolate_timestamp(1_000, 900, 0, 0), 1_000);
+    }
+
+    #[test]
+    fn interpolate_saturates_on_corrupted_timestamp() {
+        assert_eq!(
+            interpolate_timestamp(u64::MAX - 10, 6_553_500, 96, 95),
+            u64::MAX
+        );
+        assert_eq!(interpolate_timestamp(0, u64::MAX, 96, 95), u64::MAX / 95);
+    }
+}
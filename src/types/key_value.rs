@@ -0,0 +1,481 @@
+//! Key-value list used by the lidar information commands,
+//! see also [`Key-Value List`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html#lidar-information).
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, TryFromBytes, Unaligned};
+
+use super::{
+    FovCfg, LivoxLidarInstallAttitude,
+    sdk_packet::{SdkFrame, SdkPacketHeader},
+};
+use crate::error::EnumFields;
+
+/// The key of a lidar parameter, keys below `0x8000` are writable.
+///
+/// see also [`DirectLidarStateInfo`](super::DirectLidarStateInfo).
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, KnownLayout, Immutable, TryFromBytes, IntoBytes,
+)]
+#[repr(u16)]
+pub enum ParamKey {
+    PclDataType = 0x0000,
+    PatternMode = 0x0001,
+    DualEmitEn = 0x0002,
+    PointSendEn = 0x0003,
+    LidarIpCfg = 0x0004,
+    StateInfoHostIpCfg = 0x0005,
+    PointCloudHostIpCfg = 0x0006,
+    ImuHostIpCfg = 0x0007,
+    CtlHostIpCfg = 0x0008,
+    LogHostIpCfg = 0x0009,
+
+    VehicleSpeed = 0x0010,
+    EnvironmentTemp = 0x0011,
+    InstallAttitude = 0x0012,
+    BlindSpotSet = 0x0013,
+    FrameRate = 0x0014,
+    FovCfg0 = 0x0015,
+    FovCfg1 = 0x0016,
+    FovCfgEn = 0x0017,
+    DetectMode = 0x0018,
+    FuncIoCfg = 0x0019,
+    WorkTgtMode = 0x001A,
+    GlassHeat = 0x001B,
+    ImuDataEn = 0x001C,
+    FusaEn = 0x001D,
+
+    Sn = 0x8000,
+    ProductInfo = 0x8001,
+    VersionApp = 0x8002,
+    VersionLoader = 0x8003,
+    VersionHardware = 0x8004,
+    Mac = 0x8005,
+    CurWorkState = 0x8006,
+    CoreTemp = 0x8007,
+    PowerUpCnt = 0x8008,
+    LocalTimeNow = 0x8009,
+    LastSyncTime = 0x800A,
+    TimeOffset = 0x800B,
+    TimeSyncType = 0x800C,
+    StatusCode = 0x800D,
+    LidarDiagStatus = 0x800E,
+    LidarFlashStatus = 0x800F,
+    FwType = 0x8010,
+    HmsCode = 0x8011,
+    RoiMode = 0xFFFE,
+}
+
+impl ParamKey {
+    /// All the keys known by this crate.
+    pub const ALL: [ParamKey; 43] = [
+        Self::PclDataType,
+        Self::PatternMode,
+        Self::DualEmitEn,
+        Self::PointSendEn,
+        Self::LidarIpCfg,
+        Self::StateInfoHostIpCfg,
+        Self::PointCloudHostIpCfg,
+        Self::ImuHostIpCfg,
+        Self::CtlHostIpCfg,
+        Self::LogHostIpCfg,
+        Self::VehicleSpeed,
+        Self::EnvironmentTemp,
+        Self::InstallAttitude,
+        Self::BlindSpotSet,
+        Self::FrameRate,
+        Self::FovCfg0,
+        Self::FovCfg1,
+        Self::FovCfgEn,
+        Self::DetectMode,
+        Self::FuncIoCfg,
+        Self::WorkTgtMode,
+        Self::GlassHeat,
+        Self::ImuDataEn,
+        Self::FusaEn,
+        Self::Sn,
+        Self::ProductInfo,
+        Self::VersionApp,
+        Self::VersionLoader,
+        Self::VersionHardware,
+        Self::Mac,
+        Self::CurWorkState,
+        Self::CoreTemp,
+        Self::PowerUpCnt,
+        Self::LocalTimeNow,
+        Self::LastSyncTime,
+        Self::TimeOffset,
+        Self::TimeSyncType,
+        Self::StatusCode,
+        Self::LidarDiagStatus,
+        Self::LidarFlashStatus,
+        Self::FwType,
+        Self::HmsCode,
+        Self::RoiMode,
+    ];
+
+    /// Returns `true` if the parameter can be written by [`ConfigParamInfo`](super::sdk_packet::CommandID::ConfigParamInfo).
+    pub const fn is_writable(self) -> bool {
+        (self as u16) < 0x8000
+    }
+}
+
+/// Header of a key-value list.
+#[derive(Debug, Clone, Copy, KnownLayout, Immutable, Unaligned, FromBytes, IntoBytes)]
+#[repr(C, packed)]
+pub struct KeyValueListHeader {
+    /// Number of the keys in the list.
+    pub key_num: u16,
+    /// Reserved.
+    pub rsvd: u16,
+}
+
+impl EnumFields for KeyValueListHeader {}
+
+/// Header of a single key-value item, followed by `length` bytes of value.
+#[derive(Debug, Clone, Copy, KnownLayout, Immutable, Unaligned, FromBytes, IntoBytes)]
+#[repr(C, packed)]
+pub struct KeyValueHeader {
+    /// see also [`ParamKey`].
+    pub key: u16,
+    /// Length of the value in bytes.
+    pub length: u16,
+}
+
+/// Lidar IP configuration, used by key [`LidarIpCfg`](ParamKey::LidarIpCfg).
+#[derive(Debug, Clone, Copy, KnownLayout, Immutable, Unaligned, FromBytes, IntoBytes)]
+#[repr(C, packed)]
+pub struct LidarIpCfg {
+    /// IP address.
+    pub ip_addr: [u8; 4],
+    /// Subnet mask.
+    pub net_mask: [u8; 4],
+    /// Gateway address.
+    pub gw_addr: [u8; 4],
+}
+
+/// Host IP configuration of a data flow, e.g. key [`PointCloudHostIpCfg`](ParamKey::PointCloudHostIpCfg).
+#[derive(Debug, Clone, Copy, KnownLayout, Immutable, Unaligned, FromBytes, IntoBytes)]
+#[repr(C, packed)]
+pub struct HostIpCfg {
+    /// Host IP address.
+    pub ip_addr: [u8; 4],
+    /// Port on the host machine.
+    pub dst_port: u16,
+    /// Port on the lidar.
+    pub src_port: u16,
+}
+
+/// A writable lidar parameter, see also [`DirectLidarStateInfo`](super::DirectLidarStateInfo).
+#[derive(Debug, Clone)]
+pub enum ConfigParam {
+    /// see also [`PointDataType`](super::ethernet::PointDataType).
+    PclDataType(u8),
+    PatternMode(u8),
+    DualEmitEn(u8),
+    PointSendEn(u8),
+    LidarIpCfg(LidarIpCfg),
+    StateInfoHostIpCfg(HostIpCfg),
+    PointCloudHostIpCfg(HostIpCfg),
+    ImuHostIpCfg(HostIpCfg),
+    CtlHostIpCfg(HostIpCfg),
+    LogHostIpCfg(HostIpCfg),
+    VehicleSpeed(i32),
+    EnvironmentTemp(i32),
+    InstallAttitude(LivoxLidarInstallAttitude),
+    BlindSpotSet(u32),
+    FrameRate(u8),
+    FovCfg0(FovCfg),
+    FovCfg1(FovCfg),
+    FovCfgEn(u8),
+    DetectMode(u8),
+    FuncIoCfg([u8; 4]),
+    /// see also [`WorkMode`](super::WorkMode).
+    WorkTgtMode(u8),
+    GlassHeat(u8),
+    ImuDataEn(u8),
+    FusaEn(u8),
+}
+
+impl ConfigParam {
+    pub fn key(&self) -> ParamKey {
+        self.key_value().0
+    }
+
+    /// Returns the key and the encoded value of this parameter.
+    pub fn key_value(&self) -> (ParamKey, &[u8]) {
+        match self {
+            Self::PclDataType(value) => (ParamKey::PclDataType, value.as_bytes()),
+            Self::PatternMode(value) => (ParamKey::PatternMode, value.as_bytes()),
+            Self::DualEmitEn(value) => (ParamKey::DualEmitEn, value.as_bytes()),
+            Self::PointSendEn(value) => (ParamKey::PointSendEn, value.as_bytes()),
+            Self::LidarIpCfg(value) => (ParamKey::LidarIpCfg, value.as_bytes()),
+            Self::StateInfoHostIpCfg(value) => (ParamKey::StateInfoHostIpCfg, value.as_bytes()),
+            Self::PointCloudHostIpCfg(value) => (ParamKey::PointCloudHostIpCfg, value.as_bytes()),
+            Self::ImuHostIpCfg(value) => (ParamKey::ImuHostIpCfg, value.as_bytes()),
+            Self::CtlHostIpCfg(value) => (ParamKey::CtlHostIpCfg, value.as_bytes()),
+            Self::LogHostIpCfg(value) => (ParamKey::LogHostIpCfg, value.as_bytes()),
+            Self::VehicleSpeed(value) => (ParamKey::VehicleSpeed, value.as_bytes()),
+            Self::EnvironmentTemp(value) => (ParamKey::EnvironmentTemp, value.as_bytes()),
+            Self::InstallAttitude(value) => (ParamKey::InstallAttitude, value.as_bytes()),
+            Self::BlindSpotSet(value) => (ParamKey::BlindSpotSet, value.as_bytes()),
+            Self::FrameRate(value) => (ParamKey::FrameRate, value.as_bytes()),
+            Self::FovCfg0(value) => (ParamKey::FovCfg0, value.as_bytes()),
+            Self::FovCfg1(value) => (ParamKey::FovCfg1, value.as_bytes()),
+            Self::FovCfgEn(value) => (ParamKey::FovCfgEn, value.as_bytes()),
+            Self::DetectMode(value) => (ParamKey::DetectMode, value.as_bytes()),
+            Self::FuncIoCfg(value) => (ParamKey::FuncIoCfg, value.as_bytes()),
+            Self::WorkTgtMode(value) => (ParamKey::WorkTgtMode, value.as_bytes()),
+            Self::GlassHeat(value) => (ParamKey::GlassHeat, value.as_bytes()),
+            Self::ImuDataEn(value) => (ParamKey::ImuDataEn, value.as_bytes()),
+            Self::FusaEn(value) => (ParamKey::FusaEn, value.as_bytes()),
+        }
+    }
+}
+
+/// Builder of the key-value list sent by [`ConfigParamInfo`](super::sdk_packet::CommandID::ConfigParamInfo).
+#[derive(Debug, Clone)]
+pub struct KeyValueList {
+    key_num: u16,
+    buffer: Vec<u8>,
+}
+
+impl Default for KeyValueList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeyValueList {
+    pub fn new() -> Self {
+        Self {
+            key_num: 0,
+            buffer: KeyValueListHeader {
+                key_num: 0,
+                rsvd: 0,
+            }
+            .as_bytes()
+            .to_vec(),
+        }
+    }
+
+    /// Max length of the encoded list, which fills the data segment of a [`SdkFrame`].
+    pub const MAX_LEN: usize = SdkFrame::MAX_LEN - SdkPacketHeader::SIZE;
+
+    /// Appends a key with its raw encoded value.
+    ///
+    /// # Error
+    ///
+    /// Returns [`Error::FrameTooLong`](crate::Error::FrameTooLong) and leaves the list unchanged
+    /// if the list would be longer than [`MAX_LEN`](Self::MAX_LEN).
+    pub fn try_push_raw(&mut self, key: u16, value: &[u8]) -> Result<&mut Self, crate::Error> {
+        let len = self.buffer.len() + size_of::<KeyValueHeader>() + value.len();
+        if len > Self::MAX_LEN {
+            return Err(crate::Error::FrameTooLong {
+                len: SdkFrame::frame_len(len),
+                max: SdkFrame::MAX_LEN,
+            });
+        }
+        // Both fit in u16, since the list is shorter than a frame.
+        let header = KeyValueHeader {
+            key,
+            length: value.len() as u16,
+        };
+        self.buffer.extend_from_slice(header.as_bytes());
+        self.buffer.extend_from_slice(value);
+        self.key_num += 1;
+        self.buffer[..2].copy_from_slice(self.key_num.as_bytes());
+        Ok(self)
+    }
+
+    /// Same as [`try_push_raw`](Self::try_push_raw), but for a known parameter.
+    pub fn try_push(&mut self, param: &ConfigParam) -> Result<&mut Self, crate::Error> {
+        let (key, value) = param.key_value();
+        self.try_push_raw(key as u16, value)
+    }
+
+    /// Same as [`try_push`](Self::try_push) for each of the `params`, stops at the first failure.
+    pub fn try_extend<'a>(
+        &mut self,
+        params: impl IntoIterator<Item = &'a ConfigParam>,
+    ) -> Result<&mut Self, crate::Error> {
+        for param in params {
+            self.try_push(param)?;
+        }
+        Ok(self)
+    }
+
+    /// Number of the keys in the list.
+    pub const fn len(&self) -> usize {
+        self.key_num as usize
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.key_num == 0
+    }
+
+    /// The encoded list, including the [`KeyValueListHeader`].
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+}
+
+/// A borrowed key-value item of a received key-value list.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyValueRef<'a> {
+    /// Raw key, which may be unknown to this crate, see also [`param_key`](Self::param_key).
+    pub key: u16,
+    pub value: &'a [u8],
+}
+
+impl KeyValueRef<'_> {
+    /// Returns `None` if the key is unknown.
+    pub fn param_key(&self) -> Option<ParamKey> {
+        ParamKey::try_read_from_bytes(self.key.as_bytes()).ok()
+    }
+
+    /// Reads the value as `T`, fails if the length of value mismatches the size of `T`.
+    pub fn read_value<T: FromBytes>(&self) -> Result<T, crate::Error> {
+        T::read_from_bytes(self.value).map_err(|_| crate::Error::Truncated {
+            expected: size_of::<T>(),
+            found: self.value.len(),
+        })
+    }
+}
+
+/// Iterator over the items of a received key-value list.
+#[derive(Debug, Clone)]
+pub struct KeyValueIter<'a> {
+    remaining_keys: u16,
+    data: &'a [u8],
+}
+
+impl<'a> KeyValueIter<'a> {
+    /// `data` is the key-value items following the `key_num` field.
+    pub const fn new(key_num: u16, data: &'a [u8]) -> Self {
+        Self {
+            remaining_keys: key_num,
+            data,
+        }
+    }
+}
+
+impl<'a> Iterator for KeyValueIter<'a> {
+    type Item = Result<KeyValueRef<'a>, crate::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_keys == 0 {
+            return None;
+        }
+        self.remaining_keys -= 1;
+
+        let item = KeyValueHeader::read_from_prefix(self.data)
+            .map_err(|_| crate::Error::Truncated {
+                expected: size_of::<KeyValueHeader>(),
+                found: self.data.len(),
+            })
+            .and_then(|(KeyValueHeader { key, length }, rest)| {
+                let length = length as usize;
+                let (value, rest) =
+                    rest.split_at_checked(length)
+                        .ok_or(crate::Error::Truncated {
+                            expected: length,
+                            found: rest.len(),
+                        })?;
+                self.data = rest;
+                Ok(KeyValueRef { key, value })
+            });
+        if item.is_err() {
+            self.remaining_keys = 0;
+        }
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining_keys as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(list: &KeyValueList) -> Vec<(u16, Vec<u8>)> {
+        let (header, data) = KeyValueListHeader::read_from_prefix(list.as_bytes()).unwrap();
+        KeyValueIter::new(header.key_num, data)
+            .map(|item| item.map(|item| (item.key, item.value.to_vec())))
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn encode_decode() {
+        let mut list = KeyValueList::new();
+        list.try_extend(&[ConfigParam::PointSendEn(1), ConfigParam::VehicleSpeed(-2)])
+            .unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(
+            decode(&list),
+            [
+                (ParamKey::PointSendEn as u16, vec![1]),
+                (
+                    ParamKey::VehicleSpeed as u16,
+                    (-2i32).to_le_bytes().to_vec()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_value() {
+        let mut list = KeyValueList::new();
+        list.try_push_raw(0x0003, &[1, 2]).unwrap();
+        let data = &list.as_bytes()[size_of::<KeyValueListHeader>()..];
+
+        let mut iter = KeyValueIter::new(1, &data[..data.len() - 1]);
+        assert!(matches!(
+            iter.next(),
+            Some(Err(crate::Error::Truncated {
+                expected: 2,
+                found: 1
+            }))
+        ));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn list_fits_in_a_frame() {
+        let mut list = KeyValueList::new();
+        let value = vec![0; KeyValueList::MAX_LEN - list.as_bytes().len() - 4];
+        list.try_push_raw(0x8000, &value).unwrap();
+        assert_eq!(list.as_bytes().len(), KeyValueList::MAX_LEN);
+
+        assert!(matches!(
+            list.try_push_raw(0x8001, &[]),
+            Err(crate::Error::FrameTooLong {
+                len: 1404,
+                max: 1400
+            })
+        ));
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.as_bytes().len(), KeyValueList::MAX_LEN);
+    }
+
+    #[test]
+    fn value_longer_than_u16() {
+        let mut list = KeyValueList::new();
+        assert!(matches!(
+            list.try_push_raw(0x8000, &vec![0; u16::MAX as usize + 1]),
+            Err(crate::Error::FrameTooLong { .. })
+        ));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn extend_stops_beyond_a_frame() {
+        let mut list = KeyValueList::new();
+        let params = vec![ConfigParam::PointSendEn(1); KeyValueList::MAX_LEN];
+        assert!(matches!(
+            list.try_extend(&params),
+            Err(crate::Error::FrameTooLong { .. })
+        ));
+        assert_eq!(list.len(), (KeyValueList::MAX_LEN - 4) / 5);
+    }
+}
@@ -0,0 +1,237 @@
+//! The normalized point converted from any point data type.
+use super::ethernet::{CartesianHighPoint, CartesianLowPoint, SphericalPoint};
+
+/// The tag of a point, decoding the bit fields:
+/// - bit 0-1: [`spatial_confidence`](Self::spatial_confidence)
+/// - bit 2-3: [`intensity_confidence`](Self::intensity_confidence)
+/// - bit 4-5: [`return_number`](Self::return_number)
+///
+/// For details, see [`2.4 Tag Information`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html#tag-information).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(transparent)]
+pub struct PointTag(pub u8);
+
+impl From<u8> for PointTag {
+    fn from(tag: u8) -> Self {
+        Self(tag)
+    }
+}
+
+/// The confidence of a point being noise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NoiseConfidence {
+    /// Not a noise.
+    Normal,
+    /// Noise with high confidence, e.g. rain, dust, or the stray light near the lidar.
+    High,
+    Moderate,
+    /// Noise with low confidence,
+    /// which is reserved by the [`intensity_confidence`](PointTag::intensity_confidence).
+    Low,
+}
+
+impl NoiseConfidence {
+    const fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0 => Self::Normal,
+            1 => Self::High,
+            2 => Self::Moderate,
+            _ => Self::Low,
+        }
+    }
+}
+
+impl PointTag {
+    /// The confidence of the point being noise, judged by its spatial position.
+    pub const fn spatial_confidence(self) -> NoiseConfidence {
+        NoiseConfidence::from_bits(self.0)
+    }
+
+    /// The confidence of the point being noise, judged by its intensity.
+    pub const fn intensity_confidence(self) -> NoiseConfidence {
+        NoiseConfidence::from_bits(self.0 >> 2)
+    }
+
+    /// The return number of the point, in [0, 3].
+    pub const fn return_number(self) -> u8 {
+        (self.0 >> 4) & 0b11
+    }
+
+    /// Returns `true` if the point is noise with high confidence by either the spatial position or the intensity.
+    pub const fn is_noise(self) -> bool {
+        matches!(self.spatial_confidence(), NoiseConfidence::High)
+            || matches!(self.intensity_confidence(), NoiseConfidence::High)
+    }
+
+    /// Returns `true` if the point is not suspected to be noise by either the spatial position or the intensity.
+    pub const fn is_high_confidence(self) -> bool {
+        self.0 & 0b1111 == 0
+    }
+}
+
+/// The points carrying a [`PointTag`].
+pub trait Tagged {
+    fn point_tag(&self) -> PointTag;
+}
+
+impl<T: Tagged + ?Sized> Tagged for &T {
+    fn point_tag(&self) -> PointTag {
+        (**self).point_tag()
+    }
+}
+
+impl Tagged for PointTag {
+    fn point_tag(&self) -> PointTag {
+        *self
+    }
+}
+
+impl Tagged for CartesianHighPoint {
+    fn point_tag(&self) -> PointTag {
+        self.tag.into()
+    }
+}
+
+impl Tagged for CartesianLowPoint {
+    fn point_tag(&self) -> PointTag {
+        self.tag.into()
+    }
+}
+
+impl Tagged for SphericalPoint {
+    fn point_tag(&self) -> PointTag {
+        self.tag.into()
+    }
+}
+
+impl Tagged for Point {
+    fn point_tag(&self) -> PointTag {
+        self.tag
+    }
+}
+
+/// Filter adaptors of the iterators over the [`Tagged`] points, e.g. `points.iter().without_noise()`.
+pub trait TagFilterExt: Iterator<Item: Tagged> + Sized {
+    /// Drops the points which are [noise](PointTag::is_noise).
+    fn without_noise(self) -> std::iter::Filter<Self, fn(&Self::Item) -> bool> {
+        self.filter(|point| !point.point_tag().is_noise())
+    }
+
+    /// Keeps only the points with [high confidence](PointTag::is_high_confidence).
+    fn high_confidence(self) -> std::iter::Filter<Self, fn(&Self::Item) -> bool> {
+        self.filter(|point| point.point_tag().is_high_confidence())
+    }
+}
+
+impl<I: Iterator<Item: Tagged>> TagFilterExt for I {}
+
+/// A point in the cartesian coordinate, normalized from any point data type.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point {
+    /// X axis, Unit: m
+    pub x: f32,
+    /// Y axis, Unit: m
+    pub y: f32,
+    /// Z axis, Unit: m
+    pub z: f32,
+    pub reflectivity: u8,
+    pub tag: PointTag,
+}
+
+impl From<&CartesianHighPoint> for Point {
+    fn from(point: &CartesianHighPoint) -> Self {
+        let CartesianHighPoint {
+            x,
+            y,
+            z,
+            reflectivity,
+            tag,
+        } = *point;
+        Self {
+            x: x as f32 * 1e-3,
+            y: y as f32 * 1e-3,
+            z: z as f32 * 1e-3,
+            reflectivity,
+            tag: tag.into(),
+        }
+    }
+}
+
+impl From<&CartesianLowPoint> for Point {
+    fn from(point: &CartesianLowPoint) -> Self {
+        let CartesianLowPoint {
+            x,
+            y,
+            z,
+            reflectivity,
+            tag,
+        } = *point;
+        Self {
+            x: x as f32 * 1e-2,
+            y: y as f32 * 1e-2,
+            z: z as f32 * 1e-2,
+            reflectivity,
+            tag: tag.into(),
+        }
+    }
+}
+
+/// The [`theta`](SphericalPoint::theta) is the zenith angle from the Z axis,
+/// and the [`phi`](SphericalPoint::phi) is the azimuth angle from the X axis.
+impl From<&SphericalPoint> for Point {
+    fn from(point: &SphericalPoint) -> Self {
+        let SphericalPoint {
+            depth,
+            theta,
+            phi,
+            reflectivity,
+            tag,
+        } = *point;
+        let depth = depth as f32 * 1e-3;
+        let (sin_theta, cos_theta) = (theta as f32 * 0.01).to_radians().sin_cos();
+        let (sin_phi, cos_phi) = (phi as f32 * 0.01).to_radians().sin_cos();
+        Self {
+            x: depth * sin_theta * cos_phi,
+            y: depth * sin_theta * sin_phi,
+            z: depth * cos_theta,
+            reflectivity,
+            tag: tag.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_tag() {
+        // Return 2, moderate by the intensity, high by the spatial position.
+        let tag = PointTag(0b10_10_01);
+        assert_eq!(tag.spatial_confidence(), NoiseConfidence::High);
+        assert_eq!(tag.intensity_confidence(), NoiseConfidence::Moderate);
+        assert_eq!(tag.return_number(), 2);
+        assert!(tag.is_noise());
+        assert!(!tag.is_high_confidence());
+
+        let tag = PointTag(0b11_11_10);
+        assert_eq!(tag.spatial_confidence(), NoiseConfidence::Moderate);
+        assert_eq!(tag.intensity_confidence(), NoiseConfidence::Low);
+        assert_eq!(tag.return_number(), 3);
+        assert!(!tag.is_noise());
+
+        // The reserved bits 6-7 are ignored.
+        let tag = PointTag(0b11_01_00_00);
+        assert_eq!(tag.return_number(), 1);
+        assert!(tag.is_high_confidence());
+    }
+
+    #[test]
+    fn filter_by_tag() {
+        let tags = [0b00_00, 0b00_01, 0b01_00, 0b00_10, 0b10_00].map(PointTag);
+        let without_noise: Vec<_> = tags.iter().without_noise().map(|tag| tag.0).collect();
+        assert_eq!(without_noise, [0b00_00, 0b00_10, 0b10_00]);
+        let high_confidence: Vec<_> = tags.iter().high_confidence().map(|tag| tag.0).collect();
+        assert_eq!(high_confidence, [0b00_00]);
+    }
+}
@@ -1,7 +1,9 @@
-use std::fmt::Display;
+use std::{fmt::Display, mem::offset_of};
 
 use zerocopy::{Immutable, IntoBytes, KnownLayout, TryFromBytes, Unaligned};
 
+use crate::error::{EnumField, EnumFields};
+
 #[derive(Debug, Clone, Copy, Immutable, TryFromBytes, IntoBytes)]
 #[repr(u8)]
 pub enum StartOfFrame {
@@ -49,13 +51,17 @@ pub struct SdkPacketHeader {
 
 impl SdkPacketHeader {
     pub const SIZE: usize = std::mem::size_of::<Self>();
+
+    /// Creates a header with the next `seq_num` and zeroed checksums, see also [`with_checksums`](Self::with_checksums).
+    ///
+    /// The length is not checked here, use [`SdkFrame::encode`] to reject the frames longer than [`SdkFrame::MAX_LEN`].
     pub fn new(
         data_length: usize,
         cmd_id: CommandID,
         cmd_type: CommandType,
         sender_type: SendType,
     ) -> Self {
-        let length = (Self::SIZE + data_length) as u16;
+        let length = SdkFrame::frame_len(data_length) as u16;
         Self {
             sof: StartOfFrame::AA,
             version: Version::V0,
@@ -70,12 +76,245 @@ impl SdkPacketHeader {
         }
     }
     pub fn data_len(&self) -> usize {
-        self.length as usize - Self::SIZE
+        (self.length as usize).saturating_sub(Self::SIZE)
+    }
+
+    /// Fills [`crc16_h`](Self::crc16_h) and [`crc32_d`](Self::crc32_d) of the header,
+    /// `data` is the data segment following the header.
+    ///
+    /// # Example
+    ///
+    /// The broadcast [`QueryDeviceType`](CommandID::QueryDeviceType) request with `seq_num` 0:
+    ///
+    /// ```
+    /// # use livox2::types::sdk_packet::{CommandID, CommandType, SdkPacketHeader, SendType};
+    /// # use zerocopy::IntoBytes;
+    /// let mut header = SdkPacketHeader::new(0, CommandID::QueryDeviceType, CommandType::Cmd, SendType::HostSend);
+    /// header.seq_num = 0;
+    /// let header = header.with_checksums(&[]);
+    /// assert_eq!(
+    ///     header.as_bytes(),
+    ///     [
+    ///         0xAA, 0x00, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ///         0x00, 0x00, 0x00, 0x00, 0xC8, 0x64, 0x00, 0x00, 0x00, 0x00,
+    ///     ]
+    /// );
+    /// ```
+    pub fn with_checksums(mut self, data: &[u8]) -> Self {
+        self.crc16_h = self.checksum16();
+        self.crc32_d = Self::checksum32(data);
+        self
+    }
+
+    /// Verifies [`crc16_h`](Self::crc16_h) and [`crc32_d`](Self::crc32_d) of the header,
+    /// `data` is the data segment following the header.
+    ///
+    /// # Error
+    ///
+    /// Returns [`Error::CrcMismatch`](crate::Error::CrcMismatch) if either checksum does not match.
+    pub fn verify_checksums(&self, data: &[u8]) -> Result<(), crate::Error> {
+        let checksums = [
+            (self.crc16_h as u32, self.checksum16() as u32),
+            (self.crc32_d, Self::checksum32(data)),
+        ];
+        match checksums
+            .into_iter()
+            .find(|(expected, calculated)| expected != calculated)
+        {
+            Some((expected, calculated)) => Err(crate::Error::CrcMismatch {
+                expected,
+                calculated,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Parses the header of a whole frame, and returns it with the data segment
+    /// after verifying the [`length`](Self::length) and the checksums.
+    ///
+    /// # Example
+    ///
+    /// An ACK of [`QueryDeviceType`](CommandID::QueryDeviceType) in the layout of a Mid-360:
+    ///
+    /// ```
+    /// # use livox2::{Error, types::sdk_packet::SdkPacketHeader};
+    /// let mut frame = [
+    ///     0xAA, 0x00, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00,
+    ///     0x00, 0x00, 0x00, 0xEB, 0xCC, 0xC4, 0x8E, 0xBE, 0x06, 0x00, 0x09, 0x34, 0x37, 0x4D, 0x44,
+    ///     0x4C, 0x39, 0x54, 0x30, 0x30, 0x32, 0x30, 0x31, 0x39, 0x33, 0x00, 0x00, 0xC0, 0xA8, 0x01,
+    ///     0x0C, 0xC0, 0xDA,
+    /// ];
+    /// let (header, data) = SdkPacketHeader::try_ref_from_frame(&frame)?;
+    /// assert_eq!(header.data_len(), 24);
+    /// assert_eq!(&data[2..16], b"47MDL9T0020193");
+    ///
+    /// frame[47] ^= 1;
+    /// assert!(matches!(
+    ///     SdkPacketHeader::try_ref_from_frame(&frame),
+    ///     Err(Error::CrcMismatch { expected: 0x06BE8EC4, .. })
+    /// ));
+    /// # Ok::<_, Error>(())
+    /// ```
+    pub fn try_ref_from_frame(frame: &[u8]) -> Result<(&Self, &[u8]), crate::Error> {
+        let (header, data) = Self::try_ref_from_prefix(frame)?;
+        let data = data
+            .get(..header.data_len())
+            .ok_or(crate::Error::Truncated {
+                expected: header.length as usize,
+                found: frame.len(),
+            })?;
+        header.verify_checksums(data)?;
+        Ok((header, data))
+    }
+
+    /// The CRC16 of the header from [`sof`](Self::sof) to [`crc16_h`](Self::crc16_h) (not included).
+    fn checksum16(&self) -> u16 {
+        const CRC16_RANGE: usize = SdkPacketHeader::SIZE - 6;
+
+        crate::crc::CRC16.checksum(&self.as_bytes()[..CRC16_RANGE])
+    }
+
+    fn checksum32(data: &[u8]) -> u32 {
+        match data {
+            [] => 0,
+            data => crate::crc::CRC32.checksum(data),
+        }
+    }
+}
+
+impl EnumFields for SdkPacketHeader {
+    const ENUM_FIELDS: &[EnumField] = &[
+        EnumField::new::<StartOfFrame>(offset_of!(Self, sof)),
+        EnumField::new::<Version>(offset_of!(Self, version)),
+        EnumField::new::<CommandID>(offset_of!(Self, cmd_id)),
+        EnumField::new::<CommandType>(offset_of!(Self, cmd_type)),
+        EnumField::new::<SendType>(offset_of!(Self, sender_type)),
+    ];
+}
+
+/// A whole SDK frame, the [`SdkPacketHeader`] followed by the data segment.
+///
+/// # Example
+///
+/// ```
+/// # use livox2::types::sdk_packet::{CommandID, CommandType, SdkFrame, SendType};
+/// let data = [0x01, 0x00, 0x00, 0x00];
+/// let bytes = SdkFrame::encode(1, CommandID::InquireLidarInfo, CommandType::Cmd, SendType::HostSend, &data)?;
+///
+/// let frame = SdkFrame::parse(&bytes)?;
+/// assert_eq!({ frame.header.cmd_id }, CommandID::InquireLidarInfo);
+/// assert_eq!(frame.data, data);
+/// # Ok::<_, livox2::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SdkFrame<'a> {
+    pub header: &'a SdkPacketHeader,
+    pub data: &'a [u8],
+}
+
+impl<'a> SdkFrame<'a> {
+    /// Max length of a frame, including the header.
+    pub const MAX_LEN: usize = 1400;
+
+    /// Parses any frame, verifying its length and checksums, see also [`SdkPacketHeader::try_ref_from_frame`].
+    pub fn parse(frame: &'a [u8]) -> Result<Self, crate::Error> {
+        let (header, data) = SdkPacketHeader::try_ref_from_frame(frame)?;
+        Ok(Self { header, data })
+    }
+
+    /// Length of the frame with `data_len` bytes of the data segment.
+    pub const fn frame_len(data_len: usize) -> usize {
+        SdkPacketHeader::SIZE + data_len
+    }
+
+    /// Serializes a frame into a new buffer.
+    ///
+    /// The lidar replies with the same `seq_num`, which can be used to match the ACK with its request.
+    ///
+    /// # Error
+    ///
+    /// Returns [`Error::FrameTooLong`](crate::Error::FrameTooLong) if the frame is longer than [`MAX_LEN`](Self::MAX_LEN).
+    pub fn encode(
+        seq_num: u32,
+        cmd_id: CommandID,
+        cmd_type: CommandType,
+        sender_type: SendType,
+        data: &[u8],
+    ) -> Result<Vec<u8>, crate::Error> {
+        let mut buffer = vec![0; Self::frame_len(data.len()).min(Self::MAX_LEN)];
+        Self::encode_into(seq_num, cmd_id, cmd_type, sender_type, data, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Serializes the ACK of the `request` into a new buffer,
+    /// with the same `seq_num` and `cmd_id` as the `request`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use livox2::types::sdk_packet::{CommandID, CommandType, SdkFrame, SendType};
+    /// let request = SdkFrame::encode(7, CommandID::RequestRebootDevice, CommandType::Cmd, SendType::HostSend, &[0x64, 0x00])?;
+    /// let request = SdkFrame::parse(&request)?;
+    ///
+    /// let ack = SdkFrame::encode_reply(request.header, &[0x00])?;
+    /// let ack = SdkFrame::parse(&ack)?;
+    /// assert_eq!({ ack.header.seq_num }, 7);
+    /// assert_eq!({ ack.header.cmd_id }, CommandID::RequestRebootDevice);
+    /// assert_eq!({ ack.header.cmd_type }, CommandType::Ack);
+    /// assert_eq!({ ack.header.sender_type }, SendType::LidarSend);
+    /// # Ok::<_, livox2::Error>(())
+    /// ```
+    ///
+    /// # Error
+    ///
+    /// Returns [`Error::FrameTooLong`](crate::Error::FrameTooLong) if the frame is longer than [`MAX_LEN`](Self::MAX_LEN).
+    pub fn encode_reply(request: &SdkPacketHeader, data: &[u8]) -> Result<Vec<u8>, crate::Error> {
+        let sender_type = match request.sender_type {
+            SendType::HostSend => SendType::LidarSend,
+            SendType::LidarSend => SendType::HostSend,
+        };
+        Self::encode(
+            request.seq_num,
+            request.cmd_id,
+            CommandType::Ack,
+            sender_type,
+            data,
+        )
+    }
+
+    /// Serializes a frame into the beginning of the `buffer`,
+    /// returns the length of the frame.
+    ///
+    /// # Error
+    ///
+    /// Returns [`Error::FrameTooLong`](crate::Error::FrameTooLong)
+    /// if the frame is longer than [`MAX_LEN`](Self::MAX_LEN) or the `buffer`.
+    pub fn encode_into(
+        seq_num: u32,
+        cmd_id: CommandID,
+        cmd_type: CommandType,
+        sender_type: SendType,
+        data: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<usize, crate::Error> {
+        let len = Self::frame_len(data.len());
+        let max = buffer.len().min(Self::MAX_LEN);
+        if len > max {
+            return Err(crate::Error::FrameTooLong { len, max });
+        }
+
+        let mut header = SdkPacketHeader::new(data.len(), cmd_id, cmd_type, sender_type);
+        header.seq_num = seq_num;
+        let header = header.with_checksums(data);
+        let (header_bytes, data_bytes) = buffer[..len].split_at_mut(SdkPacketHeader::SIZE);
+        header_bytes.copy_from_slice(header.as_bytes());
+        data_bytes.copy_from_slice(data);
+        Ok(len)
     }
 }
 
 /// see also [`Command ID`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html#command-id)
-#[derive(Debug, Clone, Copy, Immutable, TryFromBytes, IntoBytes)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Immutable, TryFromBytes, IntoBytes)]
 #[repr(u16)]
 pub enum CommandID {
     /* Device Type Query */
@@ -147,7 +386,7 @@ impl CommandID {
     pub const COUNT: usize = 256;
 }
 
-#[derive(Debug, Clone, Copy, KnownLayout, Immutable, TryFromBytes, IntoBytes)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, KnownLayout, Immutable, TryFromBytes, IntoBytes)]
 #[repr(u8)]
 pub enum CommandType {
     /// command type, which requires response from the receiver.
@@ -156,7 +395,7 @@ pub enum CommandType {
     Ack = 1,
 }
 
-#[derive(Debug, Clone, Copy, KnownLayout, Immutable, TryFromBytes, IntoBytes)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, KnownLayout, Immutable, TryFromBytes, IntoBytes)]
 #[repr(u8)]
 pub enum SendType {
     /// command type, which requires response from the receiver.
@@ -165,6 +404,122 @@ pub enum SendType {
     LidarSend = 1,
 }
 
+/// The `ret_code` of the ACKs.
+/// For details, see [`5 Return Code Description`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html#return-code-description).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReturnCode {
+    /// Execution succeed.
+    Success,
+    /// Execution failed.
+    Failure,
+    /// Current state does not support.
+    NotPermitNow,
+    /// Setting value out of range.
+    OutOfRange,
+    /// The parameter is not supported.
+    ParamNotSupport,
+    /// Parameters need to reboot to take effect.
+    ParamRebootEffect,
+    /// The parameter is read-only and cannot be written.
+    ParamReadOnly,
+    /// The request parameter length is wrong, or the ACK exceeds the max length.
+    ParamInvalidLength,
+    /// The `key_num` mismatches the key list.
+    ParamKeyNumError,
+    /// Public key signature verification error.
+    UpgradePubKeyError,
+    /// Digest check error.
+    UpgradeDigestError,
+    /// Firmware type mismatch.
+    UpgradeFwTypeError,
+    /// Firmware length out of range.
+    UpgradeFwOutOfRange,
+    /// Not documented by the protocol.
+    Unknown(u8),
+}
+
+impl ReturnCode {
+    pub const fn is_success(self) -> bool {
+        matches!(self, Self::Success)
+    }
+
+    /// Returns [`Error::CommandFailed`](crate::Error::CommandFailed) of the `cmd_id` if the code is not [`Success`](Self::Success),
+    /// the `error_key` is the key that failed, if any.
+    pub fn check(self, cmd_id: CommandID, error_key: Option<u16>) -> Result<(), crate::Error> {
+        if self.is_success() {
+            return Ok(());
+        }
+        Err(crate::Error::CommandFailed {
+            cmd_id,
+            ret_code: self,
+            error_key,
+        })
+    }
+}
+
+impl From<u8> for ReturnCode {
+    fn from(ret_code: u8) -> Self {
+        match ret_code {
+            0x00 => Self::Success,
+            0x01 => Self::Failure,
+            0x02 => Self::NotPermitNow,
+            0x03 => Self::OutOfRange,
+            0x20 => Self::ParamNotSupport,
+            0x21 => Self::ParamRebootEffect,
+            0x22 => Self::ParamReadOnly,
+            0x23 => Self::ParamInvalidLength,
+            0x24 => Self::ParamKeyNumError,
+            0x30 => Self::UpgradePubKeyError,
+            0x31 => Self::UpgradeDigestError,
+            0x32 => Self::UpgradeFwTypeError,
+            0x33 => Self::UpgradeFwOutOfRange,
+            ret_code => Self::Unknown(ret_code),
+        }
+    }
+}
+
+impl From<ReturnCode> for u8 {
+    fn from(ret_code: ReturnCode) -> Self {
+        match ret_code {
+            ReturnCode::Success => 0x00,
+            ReturnCode::Failure => 0x01,
+            ReturnCode::NotPermitNow => 0x02,
+            ReturnCode::OutOfRange => 0x03,
+            ReturnCode::ParamNotSupport => 0x20,
+            ReturnCode::ParamRebootEffect => 0x21,
+            ReturnCode::ParamReadOnly => 0x22,
+            ReturnCode::ParamInvalidLength => 0x23,
+            ReturnCode::ParamKeyNumError => 0x24,
+            ReturnCode::UpgradePubKeyError => 0x30,
+            ReturnCode::UpgradeDigestError => 0x31,
+            ReturnCode::UpgradeFwTypeError => 0x32,
+            ReturnCode::UpgradeFwOutOfRange => 0x33,
+            ReturnCode::Unknown(ret_code) => ret_code,
+        }
+    }
+}
+
+impl Display for ReturnCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Success => write!(f, "Execution succeed"),
+            Self::Failure => write!(f, "Execution failed"),
+            Self::NotPermitNow => write!(f, "Current state does not support"),
+            Self::OutOfRange => write!(f, "Setting value out of range"),
+            Self::ParamNotSupport => write!(f, "The parameter is not supported"),
+            Self::ParamRebootEffect => write!(f, "Parameters need to reboot to take effect"),
+            Self::ParamReadOnly => write!(f, "The parameter is read-only"),
+            Self::ParamInvalidLength => write!(f, "The request parameter length is wrong"),
+            Self::ParamKeyNumError => write!(f, "The key_num mismatches the key list"),
+            Self::UpgradePubKeyError => write!(f, "Public key signature verification error"),
+            Self::UpgradeDigestError => write!(f, "Digest check error"),
+            Self::UpgradeFwTypeError => write!(f, "Firmware type mismatch"),
+            Self::UpgradeFwOutOfRange => write!(f, "Firmware length out of range"),
+            Self::Unknown(ret_code) => write!(f, "Unknown return code {ret_code:#04X}"),
+        }
+    }
+}
+
 #[derive(Debug, KnownLayout, Immutable, Unaligned, TryFromBytes, IntoBytes)]
 #[repr(C, packed)]
 pub struct QueryDeviceTypeAck {
@@ -189,7 +544,7 @@ pub struct QueryDeviceTypeAck {
     pub cmd_port: u16,
 }
 
-#[derive(Debug, Clone, Copy, Immutable, TryFromBytes, IntoBytes)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Immutable, TryFromBytes, IntoBytes)]
 #[repr(u8)]
 pub enum LivoxLidarDeviceType {
     Hub = 0,
@@ -204,9 +559,324 @@ pub enum LivoxLidarDeviceType {
     PA = 16,
 }
 
+impl EnumFields for QueryDeviceTypeAck {
+    const ENUM_FIELDS: &[EnumField] = &[EnumField::new::<LivoxLidarDeviceType>(offset_of!(
+        Self, dev_type
+    ))];
+}
+
 impl QueryDeviceTypeAck {
     #[inline]
     pub fn is_valid(&self) -> bool {
-        self.ret_code == 0
+        self.return_code().is_success()
+    }
+
+    pub fn return_code(&self) -> ReturnCode {
+        self.ret_code.into()
+    }
+
+    /// Turns a non-zero return code into [`Error::CommandFailed`](crate::Error::CommandFailed).
+    pub fn check(&self) -> Result<(), crate::Error> {
+        self.return_code().check(CommandID::QueryDeviceType, None)
+    }
+}
+
+/// Response of [`ConfigParamInfo`](CommandID::ConfigParamInfo).
+#[derive(Debug, Clone, Copy, KnownLayout, Immutable, Unaligned, TryFromBytes, IntoBytes)]
+#[repr(C, packed)]
+pub struct ConfigParamAck {
+    /// Return code
+    /// For details, see [`5 Return Code Description`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html#return-code-description)
+    pub ret_code: u8,
+    /// The key that failed to be configured, see also [`ParamKey`](super::key_value::ParamKey).
+    pub error_key: u16,
+}
+
+impl EnumFields for ConfigParamAck {}
+
+impl ConfigParamAck {
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.return_code().is_success()
+    }
+
+    pub fn return_code(&self) -> ReturnCode {
+        self.ret_code.into()
+    }
+
+    /// Turns a non-zero return code into [`Error::CommandFailed`](crate::Error::CommandFailed).
+    pub fn check(&self) -> Result<(), crate::Error> {
+        self.return_code()
+            .check(CommandID::ConfigParamInfo, Some(self.error_key))
+    }
+}
+
+/// Header of the response of [`InquireLidarInfo`](CommandID::InquireLidarInfo),
+/// followed by `key_num` key-value items.
+#[derive(Debug, Clone, Copy, KnownLayout, Immutable, Unaligned, TryFromBytes, IntoBytes)]
+#[repr(C, packed)]
+pub struct InquireLidarInfoAck {
+    /// Return code
+    /// For details, see [`5 Return Code Description`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html#return-code-description)
+    pub ret_code: u8,
+    /// Number of the keys in the response.
+    pub key_num: u16,
+}
+
+impl EnumFields for InquireLidarInfoAck {}
+
+impl InquireLidarInfoAck {
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.return_code().is_success()
+    }
+
+    pub fn return_code(&self) -> ReturnCode {
+        self.ret_code.into()
+    }
+
+    /// Turns a non-zero return code into [`Error::CommandFailed`](crate::Error::CommandFailed).
+    pub fn check(&self) -> Result<(), crate::Error> {
+        self.return_code().check(CommandID::InquireLidarInfo, None)
+    }
+}
+
+/// Response of the control commands carrying only a return code, i.e.
+/// [`RequestRebootDevice`](CommandID::RequestRebootDevice),
+/// [`RestoreFactorySettings`](CommandID::RestoreFactorySettings)
+/// and [`SetGpsTimestamp`](CommandID::SetGpsTimestamp).
+#[derive(Debug, Clone, Copy, KnownLayout, Immutable, Unaligned, TryFromBytes, IntoBytes)]
+#[repr(C, packed)]
+pub struct ControlAck {
+    /// Return code
+    /// For details, see [`5 Return Code Description`](https://livox-wiki-en.readthedocs.io/en/latest/tutorials/new_product/mid360/livox_eth_protocol_mid360.html#return-code-description)
+    pub ret_code: u8,
+}
+
+impl EnumFields for ControlAck {}
+
+impl ControlAck {
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.return_code().is_success()
+    }
+
+    pub fn return_code(&self) -> ReturnCode {
+        self.ret_code.into()
+    }
+
+    /// Turns a non-zero return code of the `cmd_id` into [`Error::CommandFailed`](crate::Error::CommandFailed).
+    pub fn check(&self, cmd_id: CommandID) -> Result<(), crate::Error> {
+        self.return_code().check(cmd_id, None)
+    }
+}
+
+/// Request of [`SetGpsTimestamp`](CommandID::SetGpsTimestamp),
+/// the data segment is the `time_type` byte followed by the time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpsTimestamp<'a> {
+    /// A `GPRMC`/`GNRMC` sentence, `time_type` 0, see also [`Rmc`](crate::nmea::Rmc).
+    Rmc(&'a str),
+    /// UTC nanoseconds since the Unix epoch in little endian, `time_type` 1.
+    UtcNanos(u64),
+}
+
+impl GpsTimestamp<'_> {
+    pub const fn time_type(&self) -> u8 {
+        match self {
+            Self::Rmc(_) => 0,
+            Self::UtcNanos(_) => 1,
+        }
+    }
+
+    /// The data segment of the request.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![self.time_type()];
+        match self {
+            Self::Rmc(sentence) => data.extend_from_slice(sentence.as_bytes()),
+            Self::UtcNanos(nanos) => data.extend_from_slice(&nanos.to_le_bytes()),
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::lidar_port::{detection::LidarSearchAckRef, discovery::DeviceInfo};
+
+    // The checksums of the frames are calculated independently of this crate.
+
+    /// The broadcast discovery request, same as the example of the protocol document.
+    const DISCOVERY_REQUEST: [u8; 24] = [
+        0xAA, 0x00, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0xC8, 0x64, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    /// The discovery ACK of a Mid-360 at `192.168.1.12`, with `seq_num` 42.
+    const DISCOVERY_ACK: [u8; 48] = [
+        0xAA, 0x00, 0x30, 0x00, 0x2A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x8D, 0x4C, 0xBA, 0x88, 0xAB, 0x4B, 0x00, 0x09, 0x34, 0x37, 0x4D, 0x44,
+        0x4C, 0x39, 0x54, 0x30, 0x30, 0x32, 0x30, 0x31, 0x39, 0x33, 0x00, 0x00, 0xC0, 0xA8, 0x01,
+        0x0C, 0x24, 0xDB,
+    ];
+
+    /// The ACK of a reboot request with `seq_num` 0x1234.
+    const REBOOT_ACK: [u8; 25] = [
+        0xAA, 0x00, 0x19, 0x00, 0x34, 0x12, 0x00, 0x00, 0x00, 0x02, 0x01, 0x01, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0xD6, 0xCF, 0x8D, 0xEF, 0x02, 0xD2, 0x00,
+    ];
+
+    #[test]
+    fn discovery_request() {
+        let (header, data) = SdkPacketHeader::try_ref_from_frame(&DISCOVERY_REQUEST).unwrap();
+        assert_eq!({ header.cmd_id }, CommandID::QueryDeviceType);
+        assert!(data.is_empty());
+
+        let mut request = SdkPacketHeader::new(
+            0,
+            CommandID::QueryDeviceType,
+            CommandType::Cmd,
+            SendType::HostSend,
+        );
+        request.seq_num = 0;
+        assert_eq!(request.with_checksums(&[]).as_bytes(), DISCOVERY_REQUEST);
+    }
+
+    #[test]
+    fn discovery_ack() {
+        let frame = SdkFrame::parse(&DISCOVERY_ACK).unwrap();
+        assert_eq!({ frame.header.seq_num }, 42);
+        assert_eq!({ frame.header.cmd_type }, CommandType::Ack);
+        assert_eq!({ frame.header.sender_type }, SendType::LidarSend);
+        assert_eq!(frame.data.len(), 24);
+        assert_eq!(&frame.data[2..16], b"47MDL9T0020193");
+
+        let ack = LidarSearchAckRef::try_from_bytes(&DISCOVERY_ACK).unwrap();
+        let device = DeviceInfo::from(&ack);
+        assert_eq!(device.dev_type, LivoxLidarDeviceType::Mid360);
+        assert_eq!(device.sn_str(), Some("47MDL9T0020193"));
+        assert_eq!(device.lidar_ip, Ipv4Addr::new(192, 168, 1, 12));
+        assert_eq!(device.cmd_port, 56100);
+
+        assert!(matches!(
+            LidarSearchAckRef::try_from_bytes(&REBOOT_ACK),
+            Err(crate::Error::WrongCommand {
+                expected: CommandID::QueryDeviceType,
+                found: CommandID::RequestRebootDevice
+            })
+        ));
+    }
+
+    #[test]
+    fn reboot_ack() {
+        let frame = SdkFrame::parse(&REBOOT_ACK).unwrap();
+        assert_eq!({ frame.header.seq_num }, 0x1234);
+        assert_eq!({ frame.header.cmd_id }, CommandID::RequestRebootDevice);
+        assert_eq!(frame.data, [0x00]);
+
+        let encoded = SdkFrame::encode(
+            0x1234,
+            CommandID::RequestRebootDevice,
+            CommandType::Ack,
+            SendType::LidarSend,
+            &[0x00],
+        )
+        .unwrap();
+        assert_eq!(encoded, REBOOT_ACK);
+    }
+
+    #[test]
+    fn truncated_frame() {
+        assert!(matches!(
+            SdkPacketHeader::try_ref_from_frame(&DISCOVERY_ACK[..47]),
+            Err(crate::Error::Truncated {
+                expected: 48,
+                found: 47
+            })
+        ));
+        assert!(matches!(
+            SdkPacketHeader::try_ref_from_frame(&DISCOVERY_ACK[..20]),
+            Err(crate::Error::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn bad_crc16() {
+        let mut frame = REBOOT_ACK;
+        // seq_num
+        frame[5] ^= 1;
+        assert!(matches!(
+            SdkPacketHeader::try_ref_from_frame(&frame),
+            Err(crate::Error::CrcMismatch {
+                expected: 0xCFD6,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn bad_crc32() {
+        let mut frame = DISCOVERY_ACK;
+        // lidar_ip
+        frame[45] ^= 1;
+        assert!(matches!(
+            SdkPacketHeader::try_ref_from_frame(&frame),
+            Err(crate::Error::CrcMismatch {
+                expected: 0x4BAB88BA,
+                ..
+            })
+        ));
+
+        let (header, data) = SdkPacketHeader::try_ref_from_prefix(&REBOOT_ACK).unwrap();
+        assert!(header.verify_checksums(data).is_ok());
+        assert!(header.verify_checksums(&[0x01]).is_err());
+        assert!(header.verify_checksums(&[]).is_err());
+    }
+
+    #[test]
+    fn bad_start_of_frame() {
+        let mut frame = REBOOT_ACK;
+        frame[0] = 0x55;
+        assert!(matches!(
+            SdkPacketHeader::try_ref_from_frame(&frame),
+            Err(crate::Error::InvalidDiscriminant {
+                offset: 0,
+                value: 0x55
+            })
+        ));
+    }
+
+    #[test]
+    fn frame_too_long() {
+        let data = [0; SdkFrame::MAX_LEN];
+        assert!(matches!(
+            SdkFrame::encode(
+                1,
+                CommandID::InquireLidarInfo,
+                CommandType::Cmd,
+                SendType::HostSend,
+                &data[..SdkFrame::MAX_LEN - SdkPacketHeader::SIZE + 1],
+            ),
+            Err(crate::Error::FrameTooLong {
+                len: 1401,
+                max: 1400
+            })
+        ));
+
+        let mut buffer = [0; 30];
+        assert!(matches!(
+            SdkFrame::encode_into(
+                1,
+                CommandID::InquireLidarInfo,
+                CommandType::Cmd,
+                SendType::HostSend,
+                &data[..10],
+                &mut buffer,
+            ),
+            Err(crate::Error::FrameTooLong { len: 34, max: 30 })
+        ));
     }
 }
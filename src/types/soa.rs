@@ -0,0 +1,346 @@
+//! Bulk conversion of the point data into the structure-of-arrays buffers.
+//!
+//! With the `simd` feature, the conversions are vectorized by AVX2 if it is detected at runtime on x86_64,
+//! otherwise they fall back to the [`scalar`] ones, which are the same as converting into [`Point`](super::point::Point)s.
+//! The vectorized spherical conversion approximates the sin and cos by polynomials,
+//! which may differ from the scalar one by a few ULPs.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod avx2;
+
+use super::ethernet::{CartesianHighPoint, CartesianLowPoint, SphericalPoint};
+
+/// The caller-provided structure-of-arrays buffers,
+/// the number of points they can hold is the shortest [`len`](Self::len) of them.
+#[derive(Debug)]
+pub struct SoaPointsMut<'a> {
+    /// Unit: m
+    pub x: &'a mut [f32],
+    /// Unit: m
+    pub y: &'a mut [f32],
+    /// Unit: m
+    pub z: &'a mut [f32],
+    /// The reflectivity.
+    pub intensity: &'a mut [f32],
+}
+
+impl SoaPointsMut<'_> {
+    pub fn len(&self) -> usize {
+        self.x
+            .len()
+            .min(self.y.len())
+            .min(self.z.len())
+            .min(self.intensity.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the buffers starting from the `index`-th point.
+    pub fn slice_from(&mut self, index: usize) -> SoaPointsMut<'_> {
+        SoaPointsMut {
+            x: &mut self.x[index..],
+            y: &mut self.y[index..],
+            z: &mut self.z[index..],
+            intensity: &mut self.intensity[index..],
+        }
+    }
+}
+
+/// The owned structure-of-arrays points.
+#[derive(Debug, Clone, Default)]
+pub struct SoaPoints {
+    /// Unit: m
+    pub x: Vec<f32>,
+    /// Unit: m
+    pub y: Vec<f32>,
+    /// Unit: m
+    pub z: Vec<f32>,
+    /// The reflectivity.
+    pub intensity: Vec<f32>,
+}
+
+impl SoaPoints {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            x: Vec::with_capacity(capacity),
+            y: Vec::with_capacity(capacity),
+            z: Vec::with_capacity(capacity),
+            intensity: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.resize(0);
+    }
+
+    /// Appends `additional` points by the `convert`, which returns the number of the converted points.
+    pub fn extend_with(
+        &mut self,
+        additional: usize,
+        convert: impl FnOnce(SoaPointsMut) -> usize,
+    ) -> usize {
+        let len = self.len();
+        self.resize(len + additional);
+        let converted = convert(self.as_mut().slice_from(len));
+        self.resize(len + converted);
+        converted
+    }
+
+    pub fn as_mut(&mut self) -> SoaPointsMut<'_> {
+        SoaPointsMut {
+            x: &mut self.x,
+            y: &mut self.y,
+            z: &mut self.z,
+            intensity: &mut self.intensity,
+        }
+    }
+
+    fn resize(&mut self, len: usize) {
+        self.x.resize(len, 0.0);
+        self.y.resize(len, 0.0);
+        self.z.resize(len, 0.0);
+        self.intensity.resize(len, 0.0);
+    }
+}
+
+/// Converts the points from mm to m, returns the number of the converted points.
+pub fn convert_cartesian_high(points: &[CartesianHighPoint], out: SoaPointsMut) -> usize {
+    let points = &points[..points.len().min(out.len())];
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if std::arch::is_x86_feature_detected!("avx2") {
+        // Safety: AVX2 is detected.
+        unsafe { avx2::convert_cartesian_high(points, out) };
+        return points.len();
+    }
+    scalar::convert_cartesian_high(points, out)
+}
+
+/// Converts the points from cm to m, returns the number of the converted points.
+pub fn convert_cartesian_low(points: &[CartesianLowPoint], out: SoaPointsMut) -> usize {
+    let points = &points[..points.len().min(out.len())];
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if std::arch::is_x86_feature_detected!("avx2") {
+        // Safety: AVX2 is detected.
+        unsafe { avx2::convert_cartesian_low(points, out) };
+        return points.len();
+    }
+    scalar::convert_cartesian_low(points, out)
+}
+
+/// Converts the points to the cartesian coordinate in m, returns the number of the converted points.
+pub fn convert_spherical(points: &[SphericalPoint], out: SoaPointsMut) -> usize {
+    let points = &points[..points.len().min(out.len())];
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if std::arch::is_x86_feature_detected!("avx2") {
+        // Safety: AVX2 is detected.
+        unsafe { avx2::convert_spherical(points, out) };
+        return points.len();
+    }
+    scalar::convert_spherical(points, out)
+}
+
+/// The scalar conversions, which are the baseline of the vectorized ones.
+pub mod scalar {
+    use super::{
+        super::{
+            ethernet::{CartesianHighPoint, CartesianLowPoint, SphericalPoint},
+            point::Point,
+        },
+        SoaPointsMut,
+    };
+
+    pub fn convert_cartesian_high(points: &[CartesianHighPoint], out: SoaPointsMut) -> usize {
+        convert(points, out)
+    }
+
+    pub fn convert_cartesian_low(points: &[CartesianLowPoint], out: SoaPointsMut) -> usize {
+        convert(points, out)
+    }
+
+    pub fn convert_spherical(points: &[SphericalPoint], out: SoaPointsMut) -> usize {
+        convert(points, out)
+    }
+
+    fn convert<'a, P: 'a>(points: &'a [P], out: SoaPointsMut) -> usize
+    where
+        Point: From<&'a P>,
+    {
+        let SoaPointsMut { x, y, z, intensity } = out;
+        let slots = x.iter_mut().zip(y).zip(z).zip(intensity);
+        slots
+            .zip(points)
+            .map(|((((x, y), z), intensity), point)| {
+                let point = Point::from(point);
+                (*x, *y, *z, *intensity) = (point.x, point.y, point.z, point.reflectivity as f32);
+            })
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Not a multiple of the SIMD lanes, so the tail is covered too.
+    const LEN: usize = 101;
+
+    /// A deterministic pseudo-random sequence.
+    fn random() -> impl FnMut() -> u32 {
+        let mut state = 0x2545_F491_u32;
+        move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        }
+    }
+
+    fn convert_both<P>(
+        points: &[P],
+        convert: fn(&[P], SoaPointsMut) -> usize,
+        scalar: fn(&[P], SoaPointsMut) -> usize,
+    ) -> (SoaPoints, SoaPoints) {
+        let mut converted = SoaPoints::with_capacity(points.len());
+        let mut expected = SoaPoints::with_capacity(points.len());
+        assert_eq!(
+            converted.extend_with(points.len(), |out| convert(points, out)),
+            points.len()
+        );
+        assert_eq!(
+            expected.extend_with(points.len(), |out| scalar(points, out)),
+            points.len()
+        );
+        (converted, expected)
+    }
+
+    fn assert_same(converted: &SoaPoints, expected: &SoaPoints) {
+        assert_eq!(converted.x, expected.x);
+        assert_eq!(converted.y, expected.y);
+        assert_eq!(converted.z, expected.z);
+        assert_eq!(converted.intensity, expected.intensity);
+    }
+
+    #[test]
+    fn cartesian_high_same_as_scalar() {
+        let mut random = random();
+        let mut points: Vec<_> = (0..LEN)
+            .map(|_| CartesianHighPoint {
+                x: random() as i32,
+                y: random() as i32 >> 12,
+                z: -(random() as i32 >> 16),
+                reflectivity: random() as u8,
+                tag: random() as u8,
+            })
+            .collect();
+        points[0].x = i32::MIN;
+        points[1].y = i32::MAX;
+
+        let (converted, expected) = convert_both(
+            &points,
+            convert_cartesian_high,
+            scalar::convert_cartesian_high,
+        );
+        assert_same(&converted, &expected);
+    }
+
+    #[test]
+    fn cartesian_low_same_as_scalar() {
+        let mut random = random();
+        let mut points: Vec<_> = (0..LEN)
+            .map(|_| CartesianLowPoint {
+                x: random() as i16,
+                y: random() as i16,
+                z: random() as i16,
+                reflectivity: random() as u8,
+                tag: random() as u8,
+            })
+            .collect();
+        points[0].z = i16::MIN;
+        points[1].z = i16::MAX;
+
+        let (converted, expected) = convert_both(
+            &points,
+            convert_cartesian_low,
+            scalar::convert_cartesian_low,
+        );
+        assert_same(&converted, &expected);
+    }
+
+    #[test]
+    fn spherical_close_to_scalar() {
+        const EDGES: [(u16, u16); 8] = [
+            (0, 0),
+            (9000, 9000),
+            (18000, 18000),
+            (4500, 27000),
+            (13500, 36000),
+            (8999, 9001),
+            (9001, 17999),
+            (17999, 35999),
+        ];
+        let mut random = random();
+        let points: Vec<_> = (0..LEN)
+            .map(|index| {
+                let (theta, phi) = EDGES
+                    .get(index)
+                    .copied()
+                    .unwrap_or_else(|| ((random() % 18001) as u16, (random() % 36001) as u16));
+                SphericalPoint {
+                    depth: random() % 200_000,
+                    theta,
+                    phi,
+                    reflectivity: random() as u8,
+                    tag: random() as u8,
+                }
+            })
+            .collect();
+
+        let (converted, expected) =
+            convert_both(&points, convert_spherical, scalar::convert_spherical);
+        assert_eq!(converted.intensity, expected.intensity);
+        for (index, point) in points.iter().enumerate() {
+            // A few ULPs of the depth.
+            let tolerance = point.depth as f32 * 1e-3 * 1e-6 + f32::EPSILON;
+            for (converted, expected) in [
+                (converted.x[index], expected.x[index]),
+                (converted.y[index], expected.y[index]),
+                (converted.z[index], expected.z[index]),
+            ] {
+                assert!(
+                    (converted - expected).abs() <= tolerance,
+                    "{point:?}: {converted} != {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn stops_at_the_shortest_buffer() {
+        let points: [_; 10] = std::array::from_fn(|_| CartesianLowPoint {
+            x: 1,
+            y: 2,
+            z: 3,
+            reflectivity: 4,
+            tag: 0,
+        });
+        let (mut x, mut y, mut z, mut intensity) = ([0.0; 10], [0.0; 10], [0.0; 4], [0.0; 10]);
+        let out = SoaPointsMut {
+            x: &mut x,
+            y: &mut y,
+            z: &mut z,
+            intensity: &mut intensity,
+        };
+        assert_eq!(convert_cartesian_low(&points, out), 4);
+        assert_eq!(z, [0.03; 4]);
+        assert_eq!(x[4..], [0.0; 6]);
+    }
+}
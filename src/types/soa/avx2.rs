@@ -0,0 +1,223 @@
+//! AVX2 kernels gathering 8 packed points at a time, the tails are left to the scalar conversions.
+use std::arch::x86_64::*;
+
+use super::{
+    super::ethernet::{CartesianHighPoint, CartesianLowPoint, SphericalPoint},
+    SoaPointsMut, scalar,
+};
+
+const LANES: usize = 8;
+
+/// Byte offsets of 8 consecutive points of the `stride`.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn offsets(stride: i32) -> __m256i {
+    _mm256_mullo_epi32(
+        _mm256_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7),
+        _mm256_set1_epi32(stride),
+    )
+}
+
+/// Gathers the 4 bytes at `offset` of each point.
+///
+/// # Safety
+///
+/// The 4 bytes at `offset` of all the 8 points must be in bounds.
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn gather(base: *const u8, offset: usize, offsets: __m256i) -> __m256i {
+    // Safety: guaranteed by the caller.
+    unsafe { _mm256_i32gather_epi32::<1>(base.add(offset).cast(), offsets) }
+}
+
+/// Extracts the byte at `shift` bits of each lane.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn byte_at<const SHIFT: i32>(value: __m256i) -> __m256 {
+    _mm256_cvtepi32_ps(_mm256_and_si256(
+        _mm256_srli_epi32::<SHIFT>(value),
+        _mm256_set1_epi32(0xFF),
+    ))
+}
+
+/// Sign extends the low 16 bits of each lane.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn low_i16(value: __m256i) -> __m256 {
+    _mm256_cvtepi32_ps(_mm256_srai_epi32::<16>(_mm256_slli_epi32::<16>(value)))
+}
+
+/// # Safety
+///
+/// The `out` must hold at least 8 points from `index`.
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn store(out: &mut SoaPointsMut, index: usize, x: __m256, y: __m256, z: __m256, i: __m256) {
+    // Safety: guaranteed by the caller.
+    unsafe {
+        _mm256_storeu_ps(out.x.as_mut_ptr().add(index), x);
+        _mm256_storeu_ps(out.y.as_mut_ptr().add(index), y);
+        _mm256_storeu_ps(out.z.as_mut_ptr().add(index), z);
+        _mm256_storeu_ps(out.intensity.as_mut_ptr().add(index), i);
+    }
+}
+
+/// # Safety
+///
+/// AVX2 must be available, and the `out` must hold all the `points`.
+#[target_feature(enable = "avx2")]
+pub(super) unsafe fn convert_cartesian_high(points: &[CartesianHighPoint], mut out: SoaPointsMut) {
+    let offsets = offsets(size_of::<CartesianHighPoint>() as i32);
+    let scale = _mm256_set1_ps(1e-3);
+    let chunks = points.len() / LANES;
+    for chunk in 0..chunks {
+        let index = chunk * LANES;
+        let base = points[index..].as_ptr().cast::<u8>();
+        // Safety: the gathered bytes are within the 8 points, x at 0, y at 4, z at 8,
+        // and the reflectivity is the 3rd byte at 10, and the `out` holds them.
+        unsafe {
+            let x = _mm256_cvtepi32_ps(gather(base, 0, offsets));
+            let y = _mm256_cvtepi32_ps(gather(base, 4, offsets));
+            let z = _mm256_cvtepi32_ps(gather(base, 8, offsets));
+            let intensity = byte_at::<16>(gather(base, 10, offsets));
+            let (x, y, z) = (
+                _mm256_mul_ps(x, scale),
+                _mm256_mul_ps(y, scale),
+                _mm256_mul_ps(z, scale),
+            );
+            store(&mut out, index, x, y, z, intensity);
+        }
+    }
+    let tail = chunks * LANES;
+    scalar::convert_cartesian_high(&points[tail..], out.slice_from(tail));
+}
+
+/// # Safety
+///
+/// AVX2 must be available, and the `out` must hold all the `points`.
+#[target_feature(enable = "avx2")]
+pub(super) unsafe fn convert_cartesian_low(points: &[CartesianLowPoint], mut out: SoaPointsMut) {
+    let offsets = offsets(size_of::<CartesianLowPoint>() as i32);
+    let scale = _mm256_set1_ps(1e-2);
+    let chunks = points.len() / LANES;
+    for chunk in 0..chunks {
+        let index = chunk * LANES;
+        let base = points[index..].as_ptr().cast::<u8>();
+        // Safety: the gathered bytes are within the 8 points, x at 0, y at 2,
+        // z and the reflectivity at 4, and the `out` holds them.
+        unsafe {
+            let x = low_i16(gather(base, 0, offsets));
+            let y = low_i16(gather(base, 2, offsets));
+            let z_intensity = gather(base, 4, offsets);
+            let z = low_i16(z_intensity);
+            let intensity = byte_at::<16>(z_intensity);
+            let (x, y, z) = (
+                _mm256_mul_ps(x, scale),
+                _mm256_mul_ps(y, scale),
+                _mm256_mul_ps(z, scale),
+            );
+            store(&mut out, index, x, y, z, intensity);
+        }
+    }
+    let tail = chunks * LANES;
+    scalar::convert_cartesian_low(&points[tail..], out.slice_from(tail));
+}
+
+/// # Safety
+///
+/// AVX2 must be available, and the `out` must hold all the `points`.
+#[target_feature(enable = "avx2")]
+pub(super) unsafe fn convert_spherical(points: &[SphericalPoint], mut out: SoaPointsMut) {
+    let offsets = offsets(size_of::<SphericalPoint>() as i32);
+    let scale = _mm256_set1_ps(1e-3);
+    let chunks = points.len() / LANES;
+    for chunk in 0..chunks {
+        let index = chunk * LANES;
+        let base = points[index..].as_ptr().cast::<u8>();
+        // Safety: the gathered bytes are within the 8 points, depth at 0, theta and phi at 4,
+        // and the reflectivity is the 3rd byte at 6, and the `out` holds them.
+        unsafe {
+            let depth = _mm256_mul_ps(_mm256_cvtepi32_ps(gather(base, 0, offsets)), scale);
+            let angles = gather(base, 4, offsets);
+            let intensity = byte_at::<16>(gather(base, 6, offsets));
+
+            let theta = _mm256_and_si256(angles, _mm256_set1_epi32(0xFFFF));
+            let phi = _mm256_srli_epi32::<16>(angles);
+            let (sin_theta, cos_theta) = sin_cos_centidegree(theta);
+            let (sin_phi, cos_phi) = sin_cos_centidegree(phi);
+
+            let radius = _mm256_mul_ps(depth, sin_theta);
+            let x = _mm256_mul_ps(radius, cos_phi);
+            let y = _mm256_mul_ps(radius, sin_phi);
+            let z = _mm256_mul_ps(depth, cos_theta);
+            store(&mut out, index, x, y, z, intensity);
+        }
+    }
+    let tail = chunks * LANES;
+    scalar::convert_spherical(&points[tail..], out.slice_from(tail));
+}
+
+/// Sin and cos of the angles in [0, 36000], Unit: 0.01 degree
+///
+/// The angle is reduced into a quadrant exactly in the integer domain,
+/// then approximated by the Taylor series on [0, π/2], whose errors are below the f32 precision.
+#[inline]
+#[target_feature(enable = "avx2")]
+fn sin_cos_centidegree(angle: __m256i) -> (__m256, __m256) {
+    const QUADRANT: f32 = 9000.0;
+
+    let angle = _mm256_cvtepi32_ps(angle);
+    let quadrant = _mm256_cvttps_epi32(_mm256_mul_ps(angle, _mm256_set1_ps(1.0 / QUADRANT)));
+    // In [0, QUADRANT] even if the quadrant is underestimated at the boundary.
+    let rest = _mm256_sub_ps(
+        angle,
+        _mm256_mul_ps(_mm256_cvtepi32_ps(quadrant), _mm256_set1_ps(QUADRANT)),
+    );
+    let t = _mm256_mul_ps(rest, _mm256_set1_ps(std::f32::consts::PI / 18000.0));
+    let t2 = _mm256_mul_ps(t, t);
+
+    let horner = |coefficients: &[f32]| {
+        coefficients
+            .iter()
+            .rev()
+            .fold(_mm256_setzero_ps(), |acc, &coefficient| {
+                _mm256_add_ps(_mm256_mul_ps(acc, t2), _mm256_set1_ps(coefficient))
+            })
+    };
+    let sin = _mm256_mul_ps(
+        t,
+        horner(&[
+            1.0,
+            -1.0 / 6.0,
+            1.0 / 120.0,
+            -1.0 / 5040.0,
+            1.0 / 362880.0,
+            -1.0 / 39916800.0,
+        ]),
+    );
+    let cos = horner(&[
+        1.0,
+        -1.0 / 2.0,
+        1.0 / 24.0,
+        -1.0 / 720.0,
+        1.0 / 40320.0,
+        -1.0 / 3628800.0,
+        1.0 / 479001600.0,
+    ]);
+
+    // Quadrant 1 and 3 swap the sin and cos,
+    // quadrant 2 and 3 negate the sin, quadrant 1 and 2 negate the cos.
+    let one = _mm256_set1_epi32(1);
+    let two = _mm256_set1_epi32(2);
+    let swap = _mm256_castsi256_ps(_mm256_cmpeq_epi32(_mm256_and_si256(quadrant, one), one));
+    let (sin, cos) = (
+        _mm256_blendv_ps(sin, cos, swap),
+        _mm256_blendv_ps(cos, sin, swap),
+    );
+    let sin_sign = _mm256_slli_epi32::<30>(_mm256_and_si256(quadrant, two));
+    let cos_sign = _mm256_slli_epi32::<30>(_mm256_and_si256(_mm256_add_epi32(quadrant, one), two));
+    (
+        _mm256_xor_ps(sin, _mm256_castsi256_ps(sin_sign)),
+        _mm256_xor_ps(cos, _mm256_castsi256_ps(cos_sign)),
+    )
+}
@@ -0,0 +1,169 @@
+//! Typed lidar state decoded from a received key-value list.
+use zerocopy::FromBytes;
+
+use super::{
+    FovCfg, LivoxLidarInstallAttitude,
+    key_value::{HostIpCfg, KeyValueRef, LidarIpCfg, ParamKey},
+};
+
+/// The lidar state, each field is `Some` only if its key has been received.
+///
+/// see also [`DirectLidarStateInfo`](super::DirectLidarStateInfo) and [`ParamKey`].
+#[derive(Debug, Clone, Default)]
+pub struct LidarStateInfo {
+    pub pcl_data_type: Option<u8>,
+    pub pattern_mode: Option<u8>,
+    pub dual_emit_en: Option<u8>,
+    pub point_send_en: Option<u8>,
+    pub lidar_ipcfg: Option<LidarIpCfg>,
+    pub state_info_host_ipcfg: Option<HostIpCfg>,
+    pub pointcloud_host_ipcfg: Option<HostIpCfg>,
+    pub imu_host_ipcfg: Option<HostIpCfg>,
+    pub ctl_host_ipcfg: Option<HostIpCfg>,
+    pub log_host_ipcfg: Option<HostIpCfg>,
+
+    pub vehicle_speed: Option<i32>,
+    pub environment_temp: Option<i32>,
+    pub install_attitude: Option<LivoxLidarInstallAttitude>,
+    pub blind_spot_set: Option<u32>,
+    pub frame_rate: Option<u8>,
+    pub fov_cfg0: Option<FovCfg>,
+    pub fov_cfg1: Option<FovCfg>,
+    pub fov_cfg_en: Option<u8>,
+    pub detect_mode: Option<u8>,
+    pub func_io_cfg: Option<[u8; 4]>,
+    pub work_tgt_mode: Option<u8>,
+    pub glass_heat: Option<u8>,
+    pub imu_data_en: Option<u8>,
+    pub fusa_en: Option<u8>,
+
+    pub sn: Option<[u8; 16]>,
+    pub product_info: Option<[u8; 64]>,
+    pub version_app: Option<[u8; 4]>,
+    pub version_loader: Option<[u8; 4]>,
+    pub version_hardware: Option<[u8; 4]>,
+    pub mac: Option<[u8; 6]>,
+    /// see also [`WorkMode`](super::WorkMode).
+    pub cur_work_state: Option<u8>,
+    /// Unit: 0.01 ℃
+    pub core_temp: Option<i32>,
+    pub powerup_cnt: Option<u32>,
+    /// Unit: ns
+    pub local_time_now: Option<u64>,
+    /// Unit: ns
+    pub last_sync_time: Option<u64>,
+    /// Unit: ns
+    pub time_offset: Option<i64>,
+    pub time_sync_type: Option<u8>,
+    pub status_code: Option<[u8; 32]>,
+    pub lidar_diag_status: Option<u16>,
+    pub lidar_flash_status: Option<u8>,
+    pub fw_type: Option<u8>,
+    pub hms_code: Option<[u32; 8]>,
+    pub roi_mode: Option<u8>,
+
+    /// Key-values that are unknown to this crate, or whose length mismatches the known type.
+    pub unknown: Vec<RawKeyValue>,
+}
+
+/// An owned key-value item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawKeyValue {
+    pub key: u16,
+    pub value: Vec<u8>,
+}
+
+impl LidarStateInfo {
+    /// Decodes the state from the key-value items, see also [`update`](Self::update).
+    pub fn from_key_values<'a>(
+        key_values: impl IntoIterator<Item = Result<KeyValueRef<'a>, crate::Error>>,
+    ) -> Result<Self, crate::Error> {
+        let mut info = Self::default();
+        for key_value in key_values {
+            info.update(key_value?);
+        }
+        Ok(info)
+    }
+
+    /// Updates the field corresponding to the key,
+    /// the key-value is preserved in [`unknown`](Self::unknown) if it can't be decoded.
+    pub fn update(&mut self, key_value: KeyValueRef<'_>) {
+        fn read<T: FromBytes>(field: &mut Option<T>, value: &[u8]) -> bool {
+            T::read_from_bytes(value)
+                .map(|value| *field = Some(value))
+                .is_ok()
+        }
+        let value = key_value.value;
+
+        let decoded = match key_value.param_key() {
+            Some(ParamKey::PclDataType) => read(&mut self.pcl_data_type, value),
+            Some(ParamKey::PatternMode) => read(&mut self.pattern_mode, value),
+            Some(ParamKey::DualEmitEn) => read(&mut self.dual_emit_en, value),
+            Some(ParamKey::PointSendEn) => read(&mut self.point_send_en, value),
+            Some(ParamKey::LidarIpCfg) => read(&mut self.lidar_ipcfg, value),
+            Some(ParamKey::StateInfoHostIpCfg) => read(&mut self.state_info_host_ipcfg, value),
+            Some(ParamKey::PointCloudHostIpCfg) => read(&mut self.pointcloud_host_ipcfg, value),
+            Some(ParamKey::ImuHostIpCfg) => read(&mut self.imu_host_ipcfg, value),
+            Some(ParamKey::CtlHostIpCfg) => read(&mut self.ctl_host_ipcfg, value),
+            Some(ParamKey::LogHostIpCfg) => read(&mut self.log_host_ipcfg, value),
+            Some(ParamKey::VehicleSpeed) => read(&mut self.vehicle_speed, value),
+            Some(ParamKey::EnvironmentTemp) => read(&mut self.environment_temp, value),
+            Some(ParamKey::InstallAttitude) => read(&mut self.install_attitude, value),
+            Some(ParamKey::BlindSpotSet) => read(&mut self.blind_spot_set, value),
+            Some(ParamKey::FrameRate) => read(&mut self.frame_rate, value),
+            Some(ParamKey::FovCfg0) => read(&mut self.fov_cfg0, value),
+            Some(ParamKey::FovCfg1) => read(&mut self.fov_cfg1, value),
+            Some(ParamKey::FovCfgEn) => read(&mut self.fov_cfg_en, value),
+            Some(ParamKey::DetectMode) => read(&mut self.detect_mode, value),
+            Some(ParamKey::FuncIoCfg) => read(&mut self.func_io_cfg, value),
+            Some(ParamKey::WorkTgtMode) => read(&mut self.work_tgt_mode, value),
+            Some(ParamKey::GlassHeat) => read(&mut self.glass_heat, value),
+            Some(ParamKey::ImuDataEn) => read(&mut self.imu_data_en, value),
+            Some(ParamKey::FusaEn) => read(&mut self.fusa_en, value),
+            Some(ParamKey::Sn) => read(&mut self.sn, value),
+            Some(ParamKey::ProductInfo) => read(&mut self.product_info, value),
+            Some(ParamKey::VersionApp) => read(&mut self.version_app, value),
+            Some(ParamKey::VersionLoader) => read(&mut self.version_loader, value),
+            Some(ParamKey::VersionHardware) => read(&mut self.version_hardware, value),
+            Some(ParamKey::Mac) => read(&mut self.mac, value),
+            Some(ParamKey::CurWorkState) => read(&mut self.cur_work_state, value),
+            Some(ParamKey::CoreTemp) => read(&mut self.core_temp, value),
+            Some(ParamKey::PowerUpCnt) => read(&mut self.powerup_cnt, value),
+            Some(ParamKey::LocalTimeNow) => read(&mut self.local_time_now, value),
+            Some(ParamKey::LastSyncTime) => read(&mut self.last_sync_time, value),
+            Some(ParamKey::TimeOffset) => read(&mut self.time_offset, value),
+            Some(ParamKey::TimeSyncType) => read(&mut self.time_sync_type, value),
+            Some(ParamKey::StatusCode) => read(&mut self.status_code, value),
+            Some(ParamKey::LidarDiagStatus) => read(&mut self.lidar_diag_status, value),
+            Some(ParamKey::LidarFlashStatus) => read(&mut self.lidar_flash_status, value),
+            Some(ParamKey::FwType) => read(&mut self.fw_type, value),
+            Some(ParamKey::HmsCode) => read(&mut self.hms_code, value),
+            Some(ParamKey::RoiMode) => read(&mut self.roi_mode, value),
+            None => false,
+        };
+        if !decoded {
+            self.unknown.push(RawKeyValue {
+                key: key_value.key,
+                value: value.to_vec(),
+            });
+        }
+    }
+
+    /// The serial number, trailing `\0` are trimmed.
+    pub fn sn_str(&self) -> Option<&str> {
+        self.sn.as_ref().and_then(|sn| trim_c_str(sn))
+    }
+
+    /// The product information, trailing `\0` are trimmed.
+    pub fn product_info_str(&self) -> Option<&str> {
+        self.product_info
+            .as_ref()
+            .and_then(|product_info| trim_c_str(product_info))
+    }
+}
+
+/// Reads a C string of a fixed-size field, up to the first `\0`, `None` if it is not UTF-8.
+pub(crate) fn trim_c_str(bytes: &[u8]) -> Option<&str> {
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    std::str::from_utf8(&bytes[..len]).ok()
+}